@@ -24,6 +24,11 @@ impl Aabb {
         tmax > tmin
     }
 
+    pub fn area(&self) -> f64 {
+        let d = self.max - self.min;
+        2.0 * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x())
+    }
+
     pub fn surrounding_box(box0: Aabb, box1: Aabb) -> Self {
         let smol_box = Vec3::min(box0.min, box1.min);
         let big_box = Vec3::max(box0.max, box1.max);
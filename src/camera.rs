@@ -1,5 +1,5 @@
 use {
-    crate::types::{Ray, Vec3},
+    crate::types::{OffsetRay, Ray, RayDifferentials, Vec3},
     rand::Rng,
 };
 
@@ -72,11 +72,41 @@ impl Camera {
         let rd = random_in_unit_disk(rng) * self.lens_radius;
         let offset = self.u * rd.x() + self.v * rd.y();
         let time = rng.gen_range(self.shutter_open..=self.shutter_close);
-        Ray::new(
-            self.origin + offset,
-            self.lower_left_corner + self.horizontal * u + self.vertical * v - self.origin - offset,
-            time,
-        )
+        Ray::new(self.origin + offset, self.direction(u, v, offset), time)
+    }
+
+    /// Same as `get_ray` along with differentials through points `du` further
+    /// along u and `dv` further along v, usually one pixel over. They go through
+    /// the same spot on the lens as the main ray
+    pub fn get_ray_differential<R: Rng + ?Sized>(
+        &self,
+        u: f64,
+        v: f64,
+        (du, dv): (f64, f64),
+        rng: &mut R,
+    ) -> Ray {
+        let rd = random_in_unit_disk(rng) * self.lens_radius;
+        let offset = self.u * rd.x() + self.v * rd.y();
+        let time = rng.gen_range(self.shutter_open..=self.shutter_close);
+        let origin = self.origin + offset;
+
+        Ray::new(origin, self.direction(u, v, offset), time).with_differentials(Some(
+            RayDifferentials {
+                x: OffsetRay {
+                    origin,
+                    direction: self.direction(u + du, v, offset),
+                },
+                y: OffsetRay {
+                    origin,
+                    direction: self.direction(u, v + dv, offset),
+                },
+            },
+        ))
+    }
+
+    /// From a spot on the lens `offset` from its center towards (u, v) on the focus plane
+    fn direction(&self, u: f64, v: f64, offset: Vec3) -> Vec3 {
+        self.lower_left_corner + self.horizontal * u + self.vertical * v - self.origin - offset
     }
 }
 
@@ -1,17 +1,21 @@
 use crate::{
     demos::{Demo, ParallelHit},
     hitable::{
-        shapes::{MovingSphere, Sphere},
-        BvhNode,
+        shapes::{Cuboid, MovingSphere, Sphere},
+        BvhNode, Hitable, Keyframe, MotionBvh,
     },
+    loaders::load_ply,
     materials::{Dielectric, Lambertian, Metal},
-    texture::{Checker, Solid},
+    texture::{Checker, Solid, VertexColor},
     types::Vec3,
     Camera,
 };
 use rand::{rngs::SmallRng, Rng, SeedableRng};
 use std::sync::Arc;
 
+/// The spheres move in a straight line so a single segment already bounds them exactly
+const MOTION_SEGMENTS: usize = 1;
+
 pub struct CheckeredMotionBlur {}
 
 impl Demo for CheckeredMotionBlur {
@@ -27,6 +31,9 @@ impl Demo for CheckeredMotionBlur {
 
     fn world(&self) -> Self::DemoT {
         let mut world: Vec<Arc<dyn ParallelHit>> = Vec::with_capacity(500);
+        // The moving spheres get their own BVH with bounds that follow them
+        // over time instead of one box stretched across their whole path
+        let mut moving: Vec<Arc<dyn ParallelHit>> = Vec::with_capacity(400);
 
         let mut rng = rand::thread_rng();
         let mut rng = SmallRng::from_rng(&mut rng).unwrap();
@@ -53,7 +60,7 @@ impl Demo for CheckeredMotionBlur {
                 if (center - l).length() > 0.9 {
                     if choose_material_probability < 0.8 {
                         // diffuse material
-                        world.push(Arc::new(MovingSphere::new(
+                        moving.push(Arc::new(MovingSphere::new(
                             center,
                             center + Vec3::new(0.0, 0.5 * rng.gen::<f64>(), 0.0),
                             0.0,
@@ -103,7 +110,50 @@ impl Demo for CheckeredMotionBlur {
             Metal::with_fuzz(Vec3::new(0.7, 0.6, 0.5), 0.0),
         )));
 
-        BvhNode::new(&mut rng, &mut world, 0.0, 1.0)
+        // Any object can be animated, here a box tumbling as it rises
+        // and a mesh spinning while it grows
+        moving.push(Arc::new(
+            Cuboid::new(
+                Vec3::splat(-0.3),
+                Vec3::splat(0.3),
+                Lambertian::new(Solid::new(Vec3::new(0.9, 0.5, 0.1))),
+            )
+            .animate(vec![
+                Keyframe::new(0.0, Vec3::new(6.5, 1.0, 1.6)),
+                Keyframe::new(1.0, Vec3::new(6.5, 1.3, 1.6))
+                    .with_rotation(Vec3::new(1.0, 1.0, 0.0), 90.0),
+            ]),
+        ));
+
+        let cube = match load_ply(
+            "assets/rgb_cube.ply",
+            Lambertian::new(VertexColor::new(Vec3::splat(0.5))),
+        ) {
+            Ok(v) => v,
+            Err(e) => panic!("error in loading ply: {}", e),
+        };
+        // The mesh has a corner at the origin, center it so it spins in place
+        moving.push(Arc::new(cube.translate(Vec3::splat(-0.5)).animate(vec![
+            Keyframe::new(0.0, Vec3::new(7.5, 1.0, 0.3)).with_scale(Vec3::splat(0.4)),
+            Keyframe::new(0.5, Vec3::new(7.5, 1.0, 0.3))
+                .with_rotation(Vec3::new(0.0, 1.0, 0.0), 45.0)
+                .with_scale(Vec3::splat(0.5)),
+            Keyframe::new(1.0, Vec3::new(7.5, 1.0, 0.3))
+                .with_rotation(Vec3::new(0.0, 1.0, 0.0), 90.0)
+                .with_scale(Vec3::splat(0.6)),
+        ])));
+
+        if !moving.is_empty() {
+            world.push(Arc::new(MotionBvh::new(
+                &mut rng,
+                &mut moving,
+                0.0,
+                1.0,
+                MOTION_SEGMENTS,
+            )));
+        }
+
+        BvhNode::new_parallel(&mut rng, &mut world, 0.0, 1.0)
     }
 
     fn camera(&self, aspect_ratio: f64) -> Camera {
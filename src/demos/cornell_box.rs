@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use crate::{
-    demos::Demo,
+    demos::{Demo, ParallelHit},
     hitable::{
         hitable_list::HitableList,
         shapes::{Cuboid, RectBuilder, Sphere},
@@ -15,6 +15,16 @@ use crate::{
 
 pub struct CornellBox {}
 
+impl CornellBox {
+    fn light() -> impl ParallelHit {
+        RectBuilder
+            .x(213.0..=343.0)
+            .z(227.0..=332.0)
+            .y(554.0)
+            .material(DiffuseLight::new(Solid::new(Vec3::splat(15))))
+    }
+}
+
 impl Demo for CornellBox {
     type DemoT = HitableList;
 
@@ -26,7 +36,6 @@ impl Demo for CornellBox {
         let red = Lambertian::new(Solid::new(Vec3::new(0.65, 0.05, 0.05)));
         let white = Lambertian::new(Solid::new(Vec3::new(0.73, 0.73, 0.73)));
         let green = Lambertian::new(Solid::new(Vec3::new(0.12, 0.45, 0.15)));
-        let light = DiffuseLight::new(Solid::new(Vec3::splat(15)));
 
         let mut objects = HitableList { list: Vec::new() };
 
@@ -44,13 +53,7 @@ impl Demo for CornellBox {
                 .x(0.0)
                 .material(red),
         ));
-        objects.push(Arc::new(
-            RectBuilder
-                .x(213.0..=343.0)
-                .z(227.0..=332.0)
-                .y(554.0)
-                .material(light),
-        ));
+        objects.push(Arc::new(Self::light()));
         objects.push(Arc::new(
             RectBuilder
                 .x(0.0..=555.0)
@@ -92,6 +95,10 @@ impl Demo for CornellBox {
         objects
     }
 
+    fn lights(&self) -> Option<Arc<dyn ParallelHit>> {
+        Some(Arc::new(Self::light()))
+    }
+
     fn camera(&self, aspect_ratio: f64) -> Camera {
         let lookfrom = Vec3::new(278.0, 278.0, -800.0);
         let lookat = Vec3::new(278.0, 278.0, 0.0);
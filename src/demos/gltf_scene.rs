@@ -0,0 +1,63 @@
+use std::sync::{Arc, OnceLock};
+
+use rand::{prelude::SmallRng, SeedableRng};
+
+use crate::{
+    demos::{Demo, ParallelHit},
+    hitable::BvhNode,
+    loaders::{load_gltf, GltfCamera, GltfScene},
+    types::Vec3,
+    Camera,
+};
+
+const SCENE: &str = "assets/scene.gltf";
+
+/// Cameras of the scene as first loaded, so `camera` doesn't have to load the
+/// whole file again after `world`
+static CAMERAS: OnceLock<Vec<GltfCamera>> = OnceLock::new();
+
+fn load() -> GltfScene {
+    match load_gltf(SCENE) {
+        Ok(v) => v,
+        Err(e) => panic!("error in loading {}: {}", SCENE, e),
+    }
+}
+
+/// A scene imported from glTF, viewed through the camera stored in the file
+pub struct GltfSceneDemo {}
+
+impl Demo for GltfSceneDemo {
+    type DemoT = BvhNode<Arc<dyn ParallelHit>>;
+
+    fn name(&self) -> &'static str {
+        "gltf_scene"
+    }
+
+    fn get_background(&self) -> Vec3 {
+        Vec3::new(0.05, 0.05, 0.08)
+    }
+
+    fn world(&self) -> Self::DemoT {
+        let scene = load();
+        // Already set if `camera` was asked for first, the file hasn't changed
+        let _ = CAMERAS.set(scene.cameras);
+
+        let mut world = scene
+            .meshes
+            .into_iter()
+            .map(|mesh| Arc::new(mesh) as Arc<dyn ParallelHit>)
+            .collect::<Vec<_>>();
+
+        let mut rng = rand::thread_rng();
+        let mut rng = SmallRng::from_rng(&mut rng).unwrap();
+
+        BvhNode::new(&mut rng, &mut world, 0.0, 1.0)
+    }
+
+    fn camera(&self, aspect_ratio: f64) -> Camera {
+        match CAMERAS.get_or_init(|| load().cameras).first() {
+            Some(camera) => camera.camera(aspect_ratio),
+            None => panic!("{} has no perspective camera", SCENE),
+        }
+    }
+}
@@ -5,8 +5,8 @@ use rand::{prelude::SmallRng, SeedableRng};
 use crate::{
     demos::{Demo, ParallelHit},
     hitable::shapes::Sphere,
-    materials::Lambertian,
-    texture::ImageTexture,
+    materials::{BumpMapped, Lambertian},
+    texture::{ColorSpace, Filter, TextureCache, WrapMode},
     types::Vec3,
     BvhNode, Camera,
 };
@@ -30,15 +30,20 @@ impl Demo for ImageTextureDemo {
         let mut rng = rand::thread_rng();
         let mut rng = SmallRng::from_rng(&mut rng).unwrap();
 
-        let earth_texture = match ImageTexture::from_filename("assets/earthmap.jpg") {
-            Ok(v) => v,
+        // u goes around the sphere so it wraps across the seam, v stops at the poles
+        let earth_texture = match TextureCache::load("assets/earthmap.jpg", ColorSpace::Srgb) {
+            Ok(v) => v
+                .with_wrap(WrapMode::Repeat, WrapMode::Clamp)
+                .with_filter(Filter::Ewa),
             Err(e) => panic!("error in creating image texture: {}", e),
         };
 
         world.push(Arc::new(Sphere::new(
             Vec3::new(0.0, 0.0, 0.0),
             2.0,
-            Lambertian::new(earth_texture),
+            // Oceans are the darkest part of the map so the brightness
+            // works as the height and the continents stand out
+            BumpMapped::new(Lambertian::new(earth_texture.clone()), earth_texture, 0.02),
         )));
 
         BvhNode::new(&mut rng, &mut world, 0.0, 1.0)
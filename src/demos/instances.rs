@@ -6,7 +6,7 @@ use crate::{
     demos::{Demo, ParallelHit},
     hitable::{
         shapes::{Cuboid, RectBuilder},
-        Hitable,
+        Instance,
     },
     materials::{DiffuseLight, Lambertian, MaterialBuilder},
     texture::Solid,
@@ -78,21 +78,21 @@ impl Demo for Instances {
                 .material(white.clone()),
         ));
 
-        // Add the two boxes
-        world.push(Arc::new(
-            Cuboid::new(
-                Vec3::new(0.0, 0.0, 0.0),
-                Vec3::new(165.0, 330.0, 165.0),
-                white.clone(),
-            )
-            .rotate_y(15.0)
-            .translate(Vec3::new(265.0, 0.0, 295.0)),
-        ));
-        world.push(Arc::new(
-            Cuboid::new(Vec3::new(0.0, 0.0, 0.0), Vec3::splat(165.0), white)
-                .rotate_y(-18.0)
-                .translate(Vec3::new(130.0, 0.0, 65.0)),
-        ));
+        // Both boxes are instances of the same unit cube. The top level BVH only
+        // stores the transforms, the geometry is shared between them.
+        let unit_cube = Arc::new(Cuboid::new(Vec3::splat(0.0), Vec3::splat(1.0), white));
+
+        let mut boxes = vec![
+            Instance::new(unit_cube.clone())
+                .scaled(Vec3::new(165.0, 330.0, 165.0))
+                .rotated_y(15.0)
+                .translated(Vec3::new(265.0, 0.0, 295.0)),
+            Instance::new(unit_cube)
+                .scaled(Vec3::splat(165.0))
+                .rotated_y(-18.0)
+                .translated(Vec3::new(130.0, 0.0, 65.0)),
+        ];
+        world.push(Arc::new(BvhNode::new(&mut rng, &mut boxes, 0.0, 1.0)));
 
         BvhNode::new(&mut rng, &mut world, 0.0, 1.0)
     }
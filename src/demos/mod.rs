@@ -1,5 +1,8 @@
+#[cfg(feature = "stats")]
+use crate::hitable::stats::{self, RayCounters};
 use crate::{
-    hitable::{hitable_list::HitableList, BvhNode, Hitable},
+    hitable::{hitable_list::HitableList, Accelerator, BvhNode, Hitable, Qbvh, Scene},
+    texture::ImageTexture,
     types::{Color, Vec3},
     Camera, HORIZONTAL_PARTITION, VERTICAL_PARTITION,
 };
@@ -10,24 +13,31 @@ use std::{
     fs::File,
     io::Write,
     sync::{Arc, Mutex},
+    time::Instant,
 };
 
 mod checkered_motion_blur;
 mod cornell_box;
 mod cornell_smoke_and_fog;
+mod gltf_scene;
 mod image_texture;
 mod instances;
 mod perlin_noise_ball;
+mod shapes;
 mod simple_light;
+mod terrain;
 mod two_spheres;
 
 pub use checkered_motion_blur::CheckeredMotionBlur;
 pub use cornell_box::CornellBox;
 pub use cornell_smoke_and_fog::CornellSmokeAndFog;
+pub use gltf_scene::GltfSceneDemo;
 pub use image_texture::ImageTextureDemo;
 pub use instances::Instances;
 pub use perlin_noise_ball::PerlinNoiseBall;
+pub use shapes::Shapes;
 pub use simple_light::SimpleLight;
+pub use terrain::Terrain;
 pub use two_spheres::TwoSpheres;
 
 #[derive(Debug)]
@@ -40,6 +50,9 @@ pub struct Chunk {
     start_x: usize,
     start_y: usize,
     buffer: Vec<u8>,
+    // Box and primitive tests done for every pixel in the chunk
+    #[cfg(feature = "stats")]
+    counters: Vec<RayCounters>,
 }
 
 impl Display for Chunk {
@@ -56,7 +69,7 @@ pub trait ParallelHit: Hitable + Send + Sync {}
 impl<T: Hitable + Send + Sync> ParallelHit for T {}
 
 pub trait Demo: Send + Sync {
-    type DemoT: Hitable + Send + Sync;
+    type DemoT: Hitable + Accelerator + Send + Sync;
 
     fn name(&self) -> &'static str;
 
@@ -68,6 +81,13 @@ pub trait Demo: Send + Sync {
         Vec3::new(0.0, 0.0, 0.0)
     }
 
+    /// Objects diffuse surfaces send rays straight towards, usually the lights.
+    /// They have to be in the world as well, this only decides where rays go.
+    /// Return `None` rather than an empty list when there is nothing to aim at
+    fn lights(&self) -> Option<Arc<dyn ParallelHit>> {
+        None
+    }
+
     fn render_chunk(&self, chunk: &mut Chunk, camera: &Camera, world: &Self::DemoT, samples: u16) {
         let &mut Chunk {
             num: _,
@@ -78,14 +98,26 @@ pub trait Demo: Send + Sync {
             start_x,
             start_y,
             ref mut buffer,
+            #[cfg(feature = "stats")]
+            ref mut counters,
         } = chunk;
         let mut offset = 0;
         let mut rng = rand::thread_rng();
         let mut rng = SmallRng::from_rng(&mut rng).unwrap();
         let background = self.get_background();
+        let lights = self.lights();
 
         assert!(buffer.len() >= nx * ny * 4);
 
+        // Throw away anything counted on this thread outside of this chunk
+        #[cfg(feature = "stats")]
+        stats::take_counters();
+
+        // Differentials a pixel apart would cover the whole pixel, with many
+        // samples each of them only needs to cover its share of it
+        let spread = 1.0 / (samples as f64).sqrt();
+        let pixel = (spread / x as f64, spread / y as f64);
+
         (start_y..start_y + ny).for_each(|j| {
             (start_x..start_x + nx).for_each(|i| {
                 let mut color = Vec3::new(0.0, 0.0, 0.0);
@@ -93,12 +125,14 @@ pub trait Demo: Send + Sync {
                     let u = (i as f64 + rng.gen::<f64>()) / x as f64;
                     let v = (j as f64 + rng.gen::<f64>()) / y as f64;
 
-                    let ray = camera.get_ray(u, v, &mut rng);
-                    color += ray.color(world, &mut rng, &background, 0);
+                    let ray = camera.get_ray_differential(u, v, pixel, &mut rng);
+                    color += ray.color(world, lights.as_deref(), &mut rng, &background, 0);
                 }
 
                 color /= samples as f64;
                 self.update_rgb(buffer, color, offset);
+                #[cfg(feature = "stats")]
+                counters.push(stats::take_counters());
                 offset += 4;
             });
         });
@@ -106,6 +140,11 @@ pub trait Demo: Send + Sync {
 
     fn render(&self, buf: &mut Vec<u8>, x: usize, y: usize, samples: u16) {
         let world = self.world();
+        println!(
+            "Demo {} Texture Memory(MiB) = {:.2}",
+            self.name(),
+            ImageTexture::memory_in_use() as f64 / (1024.0 * 1024.0)
+        );
         let delta_x = x / VERTICAL_PARTITION;
         let delta_y = y / HORIZONTAL_PARTITION;
         let remx = x % VERTICAL_PARTITION;
@@ -117,6 +156,12 @@ pub trait Demo: Send + Sync {
         let camera = self.camera(delta_x as f64 / delta_y as f64);
         let buf = Arc::new(Mutex::new(buf));
 
+        #[cfg(feature = "stats")]
+        let heat = {
+            println!("Demo {} {}", self.name(), world.stats());
+            Mutex::new(vec![RayCounters::default(); x * y])
+        };
+
         (0..VERTICAL_PARTITION).into_par_iter().for_each(|j| {
             let buf = buf.clone();
             (0..HORIZONTAL_PARTITION).into_par_iter().for_each(|i| {
@@ -144,6 +189,8 @@ pub trait Demo: Send + Sync {
                     start_x,
                     start_y,
                     buffer: vec![0; nx * ny * 4],
+                    #[cfg(feature = "stats")]
+                    counters: Vec::with_capacity(nx * ny),
                 };
 
                 println!("{}", chunk);
@@ -160,9 +207,23 @@ pub trait Demo: Send + Sync {
                     temp_offset += nx * 4;
                 }
 
+                #[cfg(feature = "stats")]
+                {
+                    let mut heat = heat.lock().unwrap();
+                    for (row, j) in (start_y..start_y + ny).enumerate() {
+                        let real_offset = (y - j - 1) * x + start_x;
+
+                        heat[real_offset..real_offset + nx]
+                            .copy_from_slice(&chunk.counters[row * nx..(row + 1) * nx]);
+                    }
+                }
+
                 println!("Rendered {}", chunk);
             });
         });
+
+        #[cfg(feature = "stats")]
+        self.save_heatmap(&heat.into_inner().unwrap(), x, y, samples);
     }
 
     #[inline]
@@ -180,6 +241,46 @@ pub trait Demo: Send + Sync {
         }
     }
 
+    /// Writes the number of box and primitive tests done for every pixel as a
+    /// heatmap image, going from black for the cheapest pixels to white for the
+    /// most expensive ones
+    #[cfg(feature = "stats")]
+    fn save_heatmap(&self, heat: &[RayCounters], width: usize, height: usize, samples: u16) {
+        let mut total = RayCounters::default();
+        for counters in heat {
+            total += *counters;
+        }
+        let rays = (width * height) as f64 * samples as f64;
+        println!(
+            "Demo {} Box Tests = {} ({:.2}/ray) Primitive Tests = {} ({:.2}/ray)",
+            self.name(),
+            total.box_tests,
+            total.box_tests as f64 / rays,
+            total.primitive_tests,
+            total.primitive_tests as f64 / rays
+        );
+
+        let tests = |counters: &RayCounters| counters.box_tests + counters.primitive_tests;
+        let max = heat.iter().map(tests).max().unwrap_or(0).max(1) as f64;
+        let image = image::RgbImage::from_fn(width as u32, height as u32, |i, j| {
+            let t = tests(&heat[j as usize * width + i as usize]) as f64 / max;
+            let channel = |offset: f64| ((3.0 * t - offset).clamp(0.0, 1.0) * 255.0) as u8;
+
+            image::Rgb([channel(0.0), channel(1.0), channel(2.0)])
+        });
+
+        let filename = format!(
+            "{}-{}x{}_{}-heatmap.png",
+            self.name(),
+            width,
+            height,
+            samples
+        );
+        if let Err(e) = image.save(&filename) {
+            panic!("couldn't write {}: {}", filename, e);
+        }
+    }
+
     fn save_as_ppm(&self, buf: &[u8], width: usize, height: usize, samples: u16) {
         let header = format!("P3\n{} {}\n255\n", width, height);
 
@@ -208,6 +309,7 @@ pub trait Demo: Send + Sync {
 pub enum DemoWrapper {
     HitableList(Box<dyn Demo<DemoT = HitableList>>),
     BVHNode(Box<dyn Demo<DemoT = BvhNode<Arc<dyn ParallelHit>>>>),
+    Scene(Box<dyn Demo<DemoT = Scene<BvhNode<Arc<dyn ParallelHit>>>>>),
 }
 
 impl DemoWrapper {
@@ -215,6 +317,7 @@ impl DemoWrapper {
         match self {
             DemoWrapper::HitableList(v) => v.name(),
             DemoWrapper::BVHNode(v) => v.name(),
+            DemoWrapper::Scene(v) => v.name(),
         }
     }
 
@@ -222,6 +325,7 @@ impl DemoWrapper {
         match self {
             DemoWrapper::HitableList(v) => v.save_as_ppm(buf, width, height, samples),
             DemoWrapper::BVHNode(v) => v.save_as_ppm(buf, width, height, samples),
+            DemoWrapper::Scene(v) => v.save_as_ppm(buf, width, height, samples),
         }
     }
 
@@ -229,6 +333,101 @@ impl DemoWrapper {
         match self {
             DemoWrapper::HitableList(v) => v.render(buf, x, y, samples),
             DemoWrapper::BVHNode(v) => v.render(buf, x, y, samples),
+            DemoWrapper::Scene(v) => v.render(buf, x, y, samples),
+        }
+    }
+
+    /// Traces the same rays through the demo's `BvhNode` and through a `Qbvh`
+    /// collapsed from it and prints how long each of them took
+    pub fn bench_accelerators(&self, x: usize, y: usize, samples: u16) {
+        match self {
+            DemoWrapper::BVHNode(demo) => bench(
+                demo.as_ref(),
+                |bvh| Qbvh::from_bvh(bvh, 0.0, 1.0),
+                x,
+                y,
+                samples,
+            ),
+            DemoWrapper::Scene(demo) => bench(
+                demo.as_ref(),
+                |scene| scene.map(|bvh| Qbvh::from_bvh(bvh, 0.0, 1.0)),
+                x,
+                y,
+                samples,
+            ),
+            DemoWrapper::HitableList(v) => {
+                println!("Skipping {}, it does not use a BVH", v.name());
+            }
         }
     }
 }
+
+/// Times the demo's world against the same world with its `BvhNode` turned
+/// into a `Qbvh` by `collapse`
+fn bench<T, U>(
+    demo: &dyn Demo<DemoT = T>,
+    collapse: impl FnOnce(T) -> U,
+    x: usize,
+    y: usize,
+    samples: u16,
+) where
+    T: Hitable + Accelerator + Send + Sync,
+    U: Hitable + Accelerator + Sync,
+{
+    let camera = demo.camera(x as f64 / y as f64);
+    let background = demo.get_background();
+    let lights = demo.lights();
+
+    let bvh = demo.world();
+    println!("Demo {} BvhNode {}", demo.name(), bvh.stats());
+    let bvh_time = trace_all(&bvh, lights.as_deref(), &camera, &background, x, y, samples);
+
+    let qbvh = collapse(bvh);
+    println!("Demo {} Qbvh {}", demo.name(), qbvh.stats());
+    let qbvh_time = trace_all(
+        &qbvh,
+        lights.as_deref(),
+        &camera,
+        &background,
+        x,
+        y,
+        samples,
+    );
+
+    println!(
+        "Demo {} BvhNode(s) = {} Qbvh(s) = {} Speedup = {:.2}x",
+        demo.name(),
+        bvh_time,
+        qbvh_time,
+        bvh_time / qbvh_time
+    );
+}
+
+/// Renders the whole frame without writing it anywhere and returns the time taken.
+/// Every row gets a fixed seed so each accelerator sees exactly the same rays.
+fn trace_all<T: Hitable + Sync>(
+    world: &T,
+    lights: Option<&dyn ParallelHit>,
+    camera: &Camera,
+    background: &Vec3,
+    x: usize,
+    y: usize,
+    samples: u16,
+) -> f64 {
+    let now = Instant::now();
+
+    (0..y).into_par_iter().for_each(|j| {
+        let mut rng = SmallRng::seed_from_u64(j as u64);
+        for i in 0..x {
+            for _s in 0..samples {
+                let u = (i as f64 + rng.gen::<f64>()) / x as f64;
+                let v = (j as f64 + rng.gen::<f64>()) / y as f64;
+
+                let ray = camera.get_ray(u, v, &mut rng);
+                ray.color(world, lights, &mut rng, background, 0);
+            }
+        }
+    });
+
+    now.elapsed().as_secs_f64()
+}
@@ -0,0 +1,400 @@
+use std::sync::Arc;
+
+use rand::{prelude::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    demos::{Demo, ParallelHit},
+    hitable::{
+        sdf::{self, Sdf, SphereTraced},
+        shapes::{
+            Cone, Cuboid, Curve, Curves, Cylinder, Disk, InfinitePlane, MeshData, Quad, Sphere,
+            Torus, Triangle, TriangleMesh,
+        },
+        AlphaTest, BvhNode, Csg, Hitable, Scene,
+    },
+    loaders::{load_obj, load_ply},
+    materials::{Hair, Lambertian},
+    texture::{Checker, ColorSpace, Filter, Solid, TextureCache, Tinted, VertexColor, WrapMode},
+    types::Vec3,
+    Aabb, Camera,
+};
+
+/// A gallery of the primitives in `hitable::shapes`
+pub struct Shapes {}
+
+impl Demo for Shapes {
+    type DemoT = Scene<BvhNode<Arc<dyn ParallelHit>>>;
+
+    fn name(&self) -> &'static str {
+        "shapes"
+    }
+
+    fn get_background(&self) -> Vec3 {
+        Vec3::new(0.7, 0.8, 1.0)
+    }
+
+    fn world(&self) -> Self::DemoT {
+        let mut world: Vec<Arc<dyn ParallelHit>> = Vec::with_capacity(16);
+
+        let mut rng = rand::thread_rng();
+        let mut rng = SmallRng::from_rng(&mut rng).unwrap();
+
+        // Floor tiled with a map of the earth. Sits just below zero so it doesn't
+        // show through the bottoms of the shapes standing on it. Trilinear
+        // filtering keeps the tiles from shimmering towards the horizon
+        let floor_texture = match TextureCache::load("assets/earthmap.jpg", ColorSpace::Srgb) {
+            Ok(v) => v
+                .with_wrap(WrapMode::Repeat, WrapMode::Repeat)
+                .with_filter(Filter::Trilinear),
+            Err(e) => panic!("error in creating image texture: {}", e),
+        };
+        world.push(Arc::new(
+            InfinitePlane::new(
+                Vec3::new(0.0, -0.001, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                Lambertian::new(Tinted::new(floor_texture, Vec3::splat(0.6))),
+            )
+            .with_tile_size(16.0),
+        ));
+
+        // Pyramid made of flat shaded triangles
+        let apex = Vec3::new(0.0, 1.5, -3.0);
+        let base = [
+            Vec3::new(-1.0, 0.0, -4.0),
+            Vec3::new(1.0, 0.0, -4.0),
+            Vec3::new(1.0, 0.0, -2.0),
+            Vec3::new(-1.0, 0.0, -2.0),
+        ];
+        let gold = Lambertian::new(Solid::new(Vec3::new(0.8, 0.6, 0.2)));
+        for i in 0..4 {
+            world.push(Arc::new(Triangle::new(
+                [base[i], base[(i + 1) % 4], apex],
+                gold.clone(),
+            )));
+        }
+
+        // A single triangle with normals bent outwards so it shades like a curved
+        // surface, textured through its per vertex uv coordinates
+        let earth_texture = match TextureCache::load("assets/earthmap.jpg", ColorSpace::Srgb) {
+            Ok(v) => v,
+            Err(e) => panic!("error in creating image texture: {}", e),
+        };
+        let vertices = [
+            Vec3::new(0.0, 0.0, -0.5),
+            Vec3::new(0.0, 0.0, 1.5),
+            Vec3::new(0.0, 2.0, 0.5),
+        ];
+        let center = Vec3::new(-1.0, 0.7, 0.5);
+        world.push(Arc::new(
+            Triangle::new(vertices, Lambertian::new(earth_texture))
+                .with_normals([
+                    (vertices[0] - center).unit_vector(),
+                    (vertices[1] - center).unit_vector(),
+                    (vertices[2] - center).unit_vector(),
+                ])
+                .with_uvs([(0.0, 0.0), (1.0, 0.0), (0.5, 1.0)]),
+        ));
+
+        // Smooth shaded torus mesh, tilted towards the camera
+        world.push(Arc::new(
+            TriangleMesh::new(
+                torus_mesh(0.8, 0.3, 48, 24),
+                Lambertian::new(Solid::new(Vec3::new(0.7, 0.15, 0.1))),
+            )
+            .rotate_z(30.0)
+            .translate(Vec3::new(0.0, 1.0, 3.0)),
+        ));
+
+        // Loaded from disk, one mesh per material used in the file
+        let octahedron = match load_obj("assets/octahedron.obj") {
+            Ok(v) => v,
+            Err(e) => panic!("error in loading obj: {}", e),
+        };
+        for mesh in octahedron {
+            world.push(Arc::new(mesh.translate(Vec3::new(-3.0, 1.0, -1.0))));
+        }
+
+        // Panel leaning back, no rotation needed to tilt a quad.
+        // Holes are cut into it with a checker pattern as the alpha
+        world.push(Arc::new(
+            Quad::new(
+                Vec3::new(-2.0, 0.0, -1.0),
+                Vec3::new(0.0, 0.0, 1.8),
+                Vec3::new(-0.8, 1.6, 0.0),
+                Lambertian::new(Solid::new(Vec3::new(0.2, 0.5, 0.6))),
+            )
+            .alpha_mask(
+                Checker::new(Solid::new(Vec3::splat(1.0)), Solid::new(Vec3::splat(0.0))),
+                AlphaTest::Threshold(0.5),
+            ),
+        ));
+
+        // Slanted box built from quads
+        world.push(Arc::new(Cuboid::from_edges(
+            Vec3::new(3.0, 0.0, -2.2),
+            Vec3::new(0.7, 0.0, 0.4),
+            Vec3::new(0.2, 0.8, -0.35),
+            Vec3::new(-0.3, 0.0, 0.5),
+            Lambertian::new(Solid::new(Vec3::new(0.6, 0.3, 0.7))),
+        )));
+
+        // Unit cube shaded with the colors stored on its vertices
+        let cube = match load_ply(
+            "assets/rgb_cube.ply",
+            Lambertian::new(VertexColor::new(Vec3::splat(0.5))),
+        ) {
+            Ok(v) => v,
+            Err(e) => panic!("error in loading ply: {}", e),
+        };
+        world.push(Arc::new(
+            cube.rotate_y(35.0).translate(Vec3::new(2.5, 0.0, 1.5)),
+        ));
+
+        // Analytic quadrics, the open cylinder shows its inside through the top
+        world.push(Arc::new(Disk::new(
+            Vec3::new(5.0, 0.001, -0.5),
+            0.6,
+            Lambertian::new(Solid::new(Vec3::new(0.9, 0.8, 0.3))),
+        )));
+        world.push(Arc::new(Cylinder::new(
+            Vec3::new(4.5, 0.0, -3.8),
+            0.4,
+            1.2,
+            Lambertian::new(Solid::new(Vec3::new(0.3, 0.6, 0.3))),
+        )));
+        world.push(Arc::new(
+            Cylinder::new(
+                Vec3::new(5.5, 0.0, 3.5),
+                0.35,
+                0.8,
+                Lambertian::new(Solid::new(Vec3::new(0.3, 0.3, 0.8))),
+            )
+            .capped(),
+        ));
+        world.push(Arc::new(
+            Cone::new(
+                Vec3::new(6.0, 0.0, -2.0),
+                0.4,
+                1.0,
+                Lambertian::new(Solid::new(Vec3::new(0.8, 0.4, 0.1))),
+            )
+            .capped(),
+        ));
+        world.push(Arc::new(
+            Torus::new(
+                Vec3::splat(0.0),
+                0.5,
+                0.15,
+                Lambertian::new(Checker::new(
+                    Solid::new(Vec3::new(0.1, 0.1, 0.1)),
+                    Solid::new(Vec3::new(0.9, 0.9, 0.9)),
+                )),
+            )
+            .rotate_x(60.0)
+            .translate(Vec3::new(6.5, 0.6, 0.9)),
+        ));
+
+        // Ellipsoid tipped over around a diagonal axis, the scale, rotation
+        // and translation all end up in a single transform
+        world.push(Arc::new(
+            Sphere::new(
+                Vec3::splat(0.0),
+                1.0,
+                Lambertian::new(Checker::new(
+                    Solid::new(Vec3::new(0.8, 0.2, 0.2)),
+                    Solid::new(Vec3::new(0.9, 0.9, 0.9)),
+                )),
+            )
+            .scale(Vec3::new(0.5, 0.25, 0.25))
+            .rotate_axis(Vec3::new(1.0, 0.0, 1.0), 40.0)
+            .translate(Vec3::new(6.3, 0.4, 2.5)),
+        ));
+
+        // Rounded die, a cube trimmed by a sphere with two pips carved into its top
+        let ivory = Lambertian::new(Solid::new(Vec3::new(0.9, 0.85, 0.75)));
+        let black = Lambertian::new(Solid::new(Vec3::new(0.1, 0.1, 0.1)));
+        let rounded = Csg::intersection(
+            Cuboid::new(Vec3::splat(-0.5), Vec3::splat(0.5), ivory.clone()),
+            Sphere::new(Vec3::splat(0.0), 0.7, ivory),
+        );
+        let pips = Csg::union(
+            Sphere::new(Vec3::new(-0.22, 0.55, -0.22), 0.12, black.clone()),
+            Sphere::new(Vec3::new(0.22, 0.55, 0.22), 0.12, black),
+        );
+        world.push(Arc::new(
+            Csg::difference(rounded, pips)
+                .rotate_y(30.0)
+                .translate(Vec3::new(7.5, 0.5, -0.8)),
+        ));
+
+        // Distance fields, a rounded box melting into a ring and a small mandelbulb
+        let blob = sdf::Cuboid::new(Vec3::new(0.35, 0.25, 0.35))
+            .rounded(0.05)
+            .smooth_union(
+                sdf::Torus::new(0.4, 0.1).translate(Vec3::new(0.0, 0.3, 0.0)),
+                0.2,
+            )
+            .smooth_difference(
+                sdf::Sphere::new(0.25).translate(Vec3::new(0.35, 0.0, 0.35)),
+                0.05,
+            )
+            .union(sdf::Capsule::new(
+                Vec3::new(0.0, 0.3, 0.0),
+                Vec3::new(0.0, 0.6, 0.0),
+                0.06,
+            ));
+        world.push(Arc::new(SphereTraced::new(
+            blob.translate(Vec3::new(4.0, 0.25, 2.2)),
+            Aabb::new(Vec3::new(3.4, 0.0, 1.6), Vec3::new(4.6, 0.95, 2.8)),
+            Lambertian::new(Solid::new(Vec3::new(0.2, 0.7, 0.7))),
+        )));
+        // Beads cut from where two spheres overlap with a hole through the
+        // middle, one with sharp rims and one with the rims rounded off
+        for (z, smoothness) in [(-2.9, 0.0), (-3.7, 0.05)] {
+            let left = sdf::Sphere::new(0.5).translate(Vec3::new(0.35, 0.0, 0.0));
+            let right = sdf::Sphere::new(0.5).translate(Vec3::new(-0.35, 0.0, 0.0));
+            let lens = if smoothness > 0.0 {
+                left.smooth_intersection(right, smoothness)
+            } else {
+                left.intersection(right)
+            };
+            let bead = lens.difference(sdf::Capsule::new(
+                Vec3::new(-0.3, 0.0, 0.0),
+                Vec3::new(0.3, 0.0, 0.0),
+                0.1,
+            ));
+            world.push(Arc::new(SphereTraced::new(
+                bead.translate(Vec3::new(6.8, 0.36, z)),
+                Aabb::new(Vec3::new(6.6, 0.0, z - 0.4), Vec3::new(7.0, 0.72, z + 0.4)),
+                Lambertian::new(Solid::new(Vec3::new(0.9, 0.5, 0.2))),
+            )));
+        }
+        world.push(Arc::new(SphereTraced::new(
+            sdf::Mandelbulb::new(8.0, 4)
+                .scale(0.3)
+                .translate(Vec3::new(8.0, 0.35, 2.2)),
+            Aabb::new(Vec3::new(7.6, 0.0, 1.8), Vec3::new(8.4, 0.7, 2.6)),
+            Lambertian::new(Solid::new(Vec3::new(0.8, 0.5, 0.6))),
+        )));
+
+        // Tuft of grass blades as ribbons that taper to a point
+        let blades = (0..1500)
+            .map(|_| {
+                let angle = rng.gen::<f64>() * 2.0 * std::f64::consts::PI;
+                let distance = 0.5 * rng.gen::<f64>().sqrt();
+                let root = Vec3::new(
+                    8.0 + distance * angle.cos(),
+                    0.0,
+                    -3.2 + distance * angle.sin(),
+                );
+                let height = 0.3 + 0.3 * rng.gen::<f64>();
+                let lean = Vec3::new(angle.cos(), 0.0, angle.sin()) * (0.2 * rng.gen::<f64>());
+                Curve::new(
+                    [
+                        root,
+                        root + Vec3::new(0.0, height / 3.0, 0.0),
+                        root + lean * 0.5 + Vec3::new(0.0, 2.0 * height / 3.0, 0.0),
+                        root + lean + Vec3::new(0.0, height, 0.0),
+                    ],
+                    (0.02, 0.0),
+                )
+            })
+            .collect();
+        world.push(Arc::new(
+            Curves::new(
+                blades,
+                Lambertian::new(Solid::new(Vec3::new(0.3, 0.6, 0.1))),
+            )
+            .ribbons(),
+        ));
+
+        // Ball of fur drooping a little under its own weight
+        let center = Vec3::new(7.9, 0.35, 1.0);
+        let strands = (0..4000)
+            .map(|_| {
+                let y = 2.0 * rng.gen::<f64>() - 1.0;
+                let angle = rng.gen::<f64>() * 2.0 * std::f64::consts::PI;
+                let ring = (1.0 - y * y).sqrt();
+                let normal = Vec3::new(ring * angle.cos(), y, ring * angle.sin());
+                let root = center + normal * 0.25;
+                let droop = Vec3::new(0.0, -0.04, 0.0);
+                Curve::new(
+                    [
+                        root,
+                        root + normal * 0.04,
+                        root + normal * 0.08 + droop * 0.5,
+                        root + normal * 0.12 + droop,
+                    ],
+                    (0.006, 0.002),
+                )
+            })
+            .collect();
+        world.push(Arc::new(Curves::new(
+            strands,
+            Hair::new(Solid::new(Vec3::new(0.45, 0.25, 0.1)), 0.2),
+        )));
+        world.push(Arc::new(Sphere::new(
+            center,
+            0.25,
+            Lambertian::new(Solid::new(Vec3::new(0.3, 0.15, 0.05))),
+        )));
+
+        Scene::new(&mut rng, world, 0.0, 1.0)
+    }
+
+    fn camera(&self, aspect_ratio: f64) -> Camera {
+        let lookfrom = Vec3::new(13.0, 3.0, 0.0);
+        let lookat = Vec3::new(0.0, 0.8, 0.0);
+        let aperture = 0.0;
+        let focus_distance = 13.0;
+        Camera::new(
+            lookfrom,
+            lookat,
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            aperture,
+            focus_distance,
+            0.0,
+            1.0,
+        )
+    }
+}
+
+/// Torus around the Y axis with per vertex normals and uvs
+fn torus_mesh(radius: f64, tube_radius: f64, segments: u32, sides: u32) -> MeshData {
+    let mut positions = Vec::with_capacity(((segments + 1) * (sides + 1)) as usize);
+    let mut normals = Vec::with_capacity(positions.capacity());
+    let mut uvs = Vec::with_capacity(positions.capacity());
+
+    // The seam vertices are repeated so uvs wrap cleanly
+    for i in 0..=segments {
+        let u = i as f64 / segments as f64;
+        let (sin_phi, cos_phi) = (u * 2.0 * std::f64::consts::PI).sin_cos();
+        let center = Vec3::new(radius * cos_phi, 0.0, radius * sin_phi);
+
+        for j in 0..=sides {
+            let v = j as f64 / sides as f64;
+            let (sin_theta, cos_theta) = (v * 2.0 * std::f64::consts::PI).sin_cos();
+            let normal = Vec3::new(cos_theta * cos_phi, sin_theta, cos_theta * sin_phi);
+
+            positions.push(center + normal * tube_radius);
+            normals.push(normal);
+            uvs.push((u, v));
+        }
+    }
+
+    let mut indices = Vec::with_capacity((segments * sides * 2) as usize);
+    for i in 0..segments {
+        for j in 0..sides {
+            let a = i * (sides + 1) + j;
+            let b = a + sides + 1;
+            indices.push([a, a + 1, b]);
+            indices.push([b, a + 1, b + 1]);
+        }
+    }
+
+    MeshData::new(positions, indices)
+        .with_normals(normals)
+        .with_uvs(uvs)
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use rand::{prelude::SmallRng, SeedableRng};
+
+use crate::{
+    demos::{Demo, ParallelHit},
+    hitable::{shapes::Heightfield, BvhNode},
+    materials::Lambertian,
+    texture::{ColorSpace, Perlin, Solid, TextureCache},
+    types::Vec3,
+    Camera,
+};
+
+/// Rolling hills from a heightfield of Perlin turbulence, with a relief map of
+/// the earth floating above them
+pub struct Terrain {}
+
+impl Demo for Terrain {
+    type DemoT = BvhNode<Arc<dyn ParallelHit>>;
+
+    fn name(&self) -> &'static str {
+        "terrain"
+    }
+
+    fn get_background(&self) -> Vec3 {
+        Vec3::new(0.7, 0.8, 1.0)
+    }
+
+    fn world(&self) -> Self::DemoT {
+        let mut world: Vec<Arc<dyn ParallelHit>> = Vec::with_capacity(2);
+
+        let mut rng = rand::thread_rng();
+        let mut rng = SmallRng::from_rng(&mut rng).unwrap();
+
+        let perlin = Perlin::new(&mut rng);
+        world.push(Arc::new(Heightfield::from_perlin(
+            &perlin,
+            (512, 512),
+            3.0,
+            Vec3::new(-50.0, 0.0, -50.0),
+            Vec3::new(100.0, 8.0, 100.0),
+            Lambertian::new(Solid::new(Vec3::new(0.35, 0.5, 0.25))),
+        )));
+
+        // Oceans are the darkest part of the map so its brightness works as
+        // the height, painted with the same map to line up the continents
+        let earth_texture = match TextureCache::load("assets/earthmap.jpg", ColorSpace::Srgb) {
+            Ok(v) => v,
+            Err(e) => panic!("error in creating image texture: {}", e),
+        };
+        match Heightfield::from_image(
+            "assets/earthmap.jpg",
+            Vec3::new(-30.0, 14.0, -15.0),
+            Vec3::new(60.0, 2.0, 30.0),
+            Lambertian::new(earth_texture),
+        ) {
+            Ok(v) => world.push(Arc::new(v)),
+            Err(e) => panic!("error in creating heightfield: {}", e),
+        }
+
+        BvhNode::new(&mut rng, &mut world, 0.0, 1.0)
+    }
+
+    fn camera(&self, aspect_ratio: f64) -> Camera {
+        let lookfrom = Vec3::new(55.0, 30.0, 55.0);
+        let lookat = Vec3::new(0.0, 2.0, 0.0);
+        let aperture = 0.0;
+        let focus_distance = 80.0;
+        Camera::new(
+            lookfrom,
+            lookat,
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            aspect_ratio,
+            aperture,
+            focus_distance,
+            0.0,
+            1.0,
+        )
+    }
+}
@@ -0,0 +1,147 @@
+use rand::prelude::SmallRng;
+
+use crate::{
+    hitable::{HitInterval, HitRecord, Hitable, INTERVAL_EPSILON},
+    types::{Ray, Vec3},
+    Aabb, Texture,
+};
+
+/// Most see through spots a single `hit` steps past before giving up, e.g.
+/// when looking along the leaves of a dense tree. A ray that runs out counts
+/// as a miss, even if there's an opaque spot further along
+const MAX_SKIPS: usize = 64;
+
+/// How `AlphaMask` decides whether a hit on a partly transparent spot counts
+#[derive(Debug, Clone, Copy)]
+pub enum AlphaTest {
+    /// Hits count where alpha is at least this much, giving hard edged cutouts
+    Threshold(f64),
+    /// Hits count with a chance equal to alpha, so partly transparent spots
+    /// average out to letting some of the light through
+    Stochastic,
+}
+
+/// Cuts holes in the surface of an object, like the gaps around the
+/// outline of a leaf or between the slats of a fence.
+///
+/// Alpha is the average of the channels of `alpha`, looked up at each hit.
+/// Hits that fail the test are ignored and the search goes on behind them,
+/// for every ray including the ones cast towards lights. The stochastic test
+/// takes its random number from a hash of the ray and the hit so asking
+/// again for the same ray gives the same answer
+pub struct AlphaMask<T, A> {
+    object: T,
+    alpha: A,
+    test: AlphaTest,
+}
+
+impl<T: Hitable, A: Texture> AlphaMask<T, A> {
+    pub fn new(object: T, alpha: A, test: AlphaTest) -> Self {
+        Self {
+            object,
+            alpha,
+            test,
+        }
+    }
+
+    fn is_opaque(&self, ray: &Ray, hit: &HitRecord) -> bool {
+        let color = self.alpha.value_at(hit);
+        let alpha = (color.x() + color.y() + color.z()) / 3.0;
+
+        match self.test {
+            AlphaTest::Threshold(threshold) => alpha >= threshold,
+            AlphaTest::Stochastic => alpha >= 1.0 || hash(ray, hit.t) < alpha,
+        }
+    }
+}
+
+impl<T: Hitable, A: Texture> Hitable for AlphaMask<T, A> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut t = t_min;
+
+        for _ in 0..MAX_SKIPS {
+            let hit = self.object.hit(ray, t, t_max)?;
+            if self.is_opaque(ray, &hit) {
+                return Some(hit);
+            }
+            t = hit.t + INTERVAL_EPSILON;
+        }
+
+        None
+    }
+
+    fn bounding_box(&self, t0: f64, t1: f64) -> Option<Aabb> {
+        self.object.bounding_box(t0, t1)
+    }
+
+    /// The boundaries of the object's intervals that fail the test are dropped
+    /// and the rest paired up again, so a hole in the surface joins the inside
+    /// up with whatever is next to it
+    fn hit_intervals(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitInterval> {
+        let boundaries = self.object.hit_intervals(ray, t_min, t_max);
+
+        let mut intervals = Vec::new();
+        let mut inside = matches!(boundaries.first(), Some(HitInterval { enter: None, .. }));
+        let mut enter = None;
+
+        for hit in boundaries
+            .into_iter()
+            .flat_map(|interval| [interval.enter, interval.exit])
+            .flatten()
+        {
+            if !self.is_opaque(ray, &hit) {
+                continue;
+            }
+
+            inside = !inside;
+            if inside {
+                enter = Some(hit);
+            } else {
+                intervals.push(HitInterval {
+                    enter: enter.take(),
+                    exit: Some(hit),
+                });
+            }
+        }
+
+        if inside {
+            intervals.push(HitInterval { enter, exit: None });
+        }
+
+        intervals
+    }
+
+    /// Sampling doesn't know about the holes, a masked light is
+    /// sampled as if it were whole
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        self.object.pdf_value(ray)
+    }
+
+    fn random(&self, origin: Vec3, time: f64, rng: &mut SmallRng) -> Vec3 {
+        self.object.random(origin, time, rng)
+    }
+}
+
+/// Number in [0, 1) made from the ray and the distance to the hit
+fn hash(ray: &Ray, t: f64) -> f64 {
+    let values = [
+        ray.origin.x(),
+        ray.origin.y(),
+        ray.origin.z(),
+        ray.direction.x(),
+        ray.direction.y(),
+        ray.direction.z(),
+        t,
+    ];
+
+    // SplitMix64 finalizer over each value in turn
+    let mut h: u64 = 0x9e37_79b9_7f4a_7c15;
+    for value in values {
+        h ^= value.to_bits();
+        h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        h ^= h >> 31;
+    }
+
+    (h >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -0,0 +1,199 @@
+use rand::prelude::SmallRng;
+
+use crate::{
+    hitable::{
+        transform::{to_object, to_object_pdf, to_world, transform_box},
+        HitInterval, HitRecord, Hitable,
+    },
+    types::{Mat4, Quaternion, Ray, Vec3},
+    Aabb,
+};
+
+/// Pieces each stretch between keyframes is cut into when bounding the motion
+const BOUND_STEPS: usize = 16;
+
+/// Where an object is at a point in time. The object is scaled first,
+/// then rotated and then moved to `translation`
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe {
+    time: f64,
+    translation: Vec3,
+    rotation: Quaternion,
+    scale: Vec3,
+}
+
+impl Keyframe {
+    pub fn new(time: f64, translation: Vec3) -> Self {
+        Self {
+            time,
+            translation,
+            rotation: Quaternion::identity(),
+            scale: Vec3::splat(1.0),
+        }
+    }
+
+    /// Rotation by `angle` degrees around `axis`, like `Hitable::rotate_axis`
+    pub fn with_rotation(mut self, axis: Vec3, angle: f64) -> Self {
+        self.rotation = Quaternion::from_axis_angle(axis, angle);
+        self
+    }
+
+    /// Scale along each axis. None of the components should be zero
+    pub fn with_scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    fn matrix(&self) -> Mat4 {
+        Mat4::translation(self.translation) * self.rotation.to_mat4() * Mat4::scaling(self.scale)
+    }
+
+    fn inverse(&self) -> Mat4 {
+        Mat4::scaling(Vec3::splat(1.0) / self.scale)
+            * self.rotation.conjugate().to_mat4()
+            * Mat4::translation(-self.translation)
+    }
+}
+
+/// An object that moves, turns and changes size over time, for motion blur.
+///
+/// The transform is interpolated between the keyframes at the time of each ray,
+/// linearly for the translation and scale and with `Quaternion::slerp` for the
+/// rotation. It stays put before the first and after the last keyframe.
+/// Otherwise works like `Transform`
+pub struct AnimatedTransform<T> {
+    object: T,
+    keyframes: Vec<Keyframe>,
+}
+
+impl<T: Hitable> AnimatedTransform<T> {
+    pub fn new(object: T, mut keyframes: Vec<Keyframe>) -> Self {
+        assert!(
+            !keyframes.is_empty(),
+            "animation needs at least one keyframe"
+        );
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Self { object, keyframes }
+    }
+
+    /// The keyframe the animation passes through at `time`
+    fn at(&self, time: f64) -> Keyframe {
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        if next == 0 {
+            return self.keyframes[0];
+        }
+        if next == self.keyframes.len() {
+            return self.keyframes[next - 1];
+        }
+
+        let (a, b) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let t = (time - a.time) / (b.time - a.time);
+
+        Keyframe {
+            time,
+            translation: a.translation + (b.translation - a.translation) * t,
+            rotation: a.rotation.slerp(b.rotation, t),
+            scale: a.scale + (b.scale - a.scale) * t,
+        }
+    }
+}
+
+impl<T: Hitable> Hitable for AnimatedTransform<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let keyframe = self.at(ray.time());
+        let inverse = keyframe.inverse();
+
+        let mut hit = self.object.hit(&to_object(&inverse, ray), t_min, t_max)?;
+        to_world(&keyframe.matrix(), &inverse.transpose(), &mut hit);
+
+        Some(hit)
+    }
+
+    /// Boxes at a number of points in time merged together. Between those points
+    /// translation and scale move the corners in straight lines, which the merged
+    /// boxes already cover, rotations bend those lines into arcs so the box is
+    /// padded by how far the arcs can stray
+    fn bounding_box(&self, t0: f64, t1: f64) -> Option<Aabb> {
+        let bbox = self.object.bounding_box(t0, t1)?;
+
+        // Sample more densely between keyframes inside the interval
+        let mut times = vec![t0];
+        times.extend(
+            self.keyframes
+                .iter()
+                .map(|k| k.time)
+                .filter(|&time| time > t0 && time < t1),
+        );
+        times.push(t1);
+
+        let samples = times
+            .windows(2)
+            .flat_map(|w| {
+                (0..BOUND_STEPS).map(move |i| w[0] + (w[1] - w[0]) * i as f64 / BOUND_STEPS as f64)
+            })
+            .chain(std::iter::once(t1))
+            .map(|time| self.at(time))
+            .collect::<Vec<_>>();
+
+        // Farthest a corner of the object gets from its origin
+        let reach = Vec3::max(-bbox.min, bbox.max).length();
+
+        let largest = |v: Vec3| v.x().abs().max(v.y().abs()).max(v.z().abs());
+
+        let mut padding: f64 = 0.0;
+        for w in samples.windows(2) {
+            let angle = w[0].rotation.angle_to(&w[1].rotation);
+            if angle == 0.0 {
+                continue;
+            }
+
+            // The arc strays from its chord by up to r (1 - cos(angle / 2)), and the
+            // change in scale turns by up to the angle on top of that
+            let scale = largest(w[0].scale).max(largest(w[1].scale));
+            let scale_change = largest(w[1].scale - w[0].scale);
+            padding = padding
+                .max(reach * scale * (1.0 - (angle / 2.0).cos()) + reach * scale_change * angle);
+        }
+
+        let bbox = samples
+            .iter()
+            .map(|keyframe| transform_box(&keyframe.matrix(), &bbox))
+            .reduce(Aabb::surrounding_box)?;
+
+        Some(Aabb::new(
+            bbox.min - Vec3::splat(padding),
+            bbox.max + Vec3::splat(padding),
+        ))
+    }
+
+    fn hit_intervals(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitInterval> {
+        let keyframe = self.at(ray.time());
+        let (matrix, inverse) = (keyframe.matrix(), keyframe.inverse());
+        let normal_matrix = inverse.transpose();
+
+        let mut intervals = self
+            .object
+            .hit_intervals(&to_object(&inverse, ray), t_min, t_max);
+        for interval in intervals.iter_mut() {
+            for hit in interval.enter.iter_mut().chain(interval.exit.iter_mut()) {
+                to_world(&matrix, &normal_matrix, hit);
+            }
+        }
+
+        intervals
+    }
+
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        let (object_ray, stretch) = to_object_pdf(&self.at(ray.time()).inverse(), ray);
+        self.object.pdf_value(&object_ray) * stretch
+    }
+
+    fn random(&self, origin: Vec3, time: f64, rng: &mut SmallRng) -> Vec3 {
+        let keyframe = self.at(time);
+        let direction = self
+            .object
+            .random(keyframe.inverse().transform_point(origin), time, rng);
+        keyframe.matrix().transform_vector(direction)
+    }
+}
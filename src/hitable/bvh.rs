@@ -1,13 +1,26 @@
 use std::cmp::Ordering;
 
-use rand::{prelude::SliceRandom, Rng};
+use rand::{
+    prelude::{SliceRandom, SmallRng},
+    Rng, SeedableRng,
+};
+use rayon::prelude::*;
 
 use crate::{
-    hitable::{HitRecord, Hitable},
+    hitable::{stats, Accelerator, BuildStats, HitRecord, Hitable},
     types::Ray,
     Aabb,
 };
 
+/// Slices smaller than this are built on the current thread. Below this size
+/// the overhead of spawning rayon tasks outweighs the work being split.
+const PARALLEL_BUILD_THRESHOLD: usize = 4096;
+
+/// Orders two objects along one axis using their bounds swept over `t0..t1`,
+/// so moving objects are placed by everywhere they go during the shutter
+/// interval instead of where they are at time zero.
+type Comparator<T> = fn(&T, &T, f64, f64) -> Ordering;
+
 pub struct BvhNode<T: Hitable> {
     bounding_box: Aabb,
     left: HitNode<T>,
@@ -15,58 +28,93 @@ pub struct BvhNode<T: Hitable> {
 }
 
 impl<T: Hitable + Clone> BvhNode<T> {
+    /// Panics if `objects` is empty or any of them has no bounding box, like an
+    /// `InfinitePlane`. Put those in a `Scene` which keeps them out of the tree
     pub fn new<R: Rng + ?Sized>(rng: &mut R, objects: &mut [T], t0: f64, t1: f64) -> Self {
-        let comparator = [
-            Self::box_x_compare,
-            Self::box_y_compare,
-            Self::box_z_compare,
-        ]
-        .choose(rng)
-        .unwrap();
+        let mut rng = SmallRng::from_rng(rng).expect("error in seeding BVH builder");
 
-        let (left, right) = match objects.len() {
-            1 => (
-                HitNode::Direct(objects[0].clone()),
-                HitNode::Direct(objects[0].clone()),
-            ),
-            2 => match comparator(&objects[0], &objects[1]) {
-                Ordering::Greater => (
-                    HitNode::Direct(objects[1].clone()),
-                    HitNode::Direct(objects[0].clone()),
-                ),
-                _ => (
-                    HitNode::Direct(objects[0].clone()),
-                    HitNode::Direct(objects[1].clone()),
-                ),
-            },
+        Self::build(&mut rng, objects, t0, t1)
+    }
+
+    fn build(rng: &mut SmallRng, objects: &mut [T], t0: f64, t1: f64) -> Self {
+        let comparator = Self::pick_comparator(rng);
 
+        let (left, right) = match objects.len() {
+            1 | 2 => Self::leaves(comparator, objects, t0, t1),
             n => {
-                objects.sort_by(comparator);
+                let (mut left_rng, mut right_rng) = Self::split_rng(rng);
+
+                objects.sort_by(|a, b| comparator(a, b, t0, t1));
                 let (l, r) = objects.split_at_mut(n / 2);
                 (
-                    HitNode::Bvh(Box::new(BvhNode::new(rng, l, t0, t1))),
-                    HitNode::Bvh(Box::new(BvhNode::new(rng, r, t0, t1))),
+                    HitNode::Bvh(Box::new(Self::build(&mut left_rng, l, t0, t1))),
+                    HitNode::Bvh(Box::new(Self::build(&mut right_rng, r, t0, t1))),
                 )
             }
         };
 
+        Self::from_children(left, right, t0, t1)
+    }
+
+    fn leaves(
+        comparator: Comparator<T>,
+        objects: &[T],
+        t0: f64,
+        t1: f64,
+    ) -> (HitNode<T>, HitNode<T>) {
+        match objects {
+            [a] => (HitNode::Direct(a.clone()), HitNode::Empty),
+            [a, b] => match comparator(a, b, t0, t1) {
+                Ordering::Greater => (HitNode::Direct(b.clone()), HitNode::Direct(a.clone())),
+                _ => (HitNode::Direct(a.clone()), HitNode::Direct(b.clone())),
+            },
+            _ => unreachable!("leaves are only built from one or two objects"),
+        }
+    }
+
+    fn from_children(left: HitNode<T>, right: HitNode<T>, t0: f64, t1: f64) -> Self {
         let left_box = left
             .bounding_box(t0, t1)
-            .expect("missing bounding box for left BVH Node");
-        let right_box = right
-            .bounding_box(t0, t1)
-            .expect("missing bounding box for right BVH Node");
+            .expect("missing bounding box for left BVH Node, unbounded objects belong in a Scene");
+        let bounding_box = match right {
+            HitNode::Empty => left_box,
+            _ => {
+                let right_box = right.bounding_box(t0, t1).expect(
+                    "missing bounding box for right BVH Node, unbounded objects belong in a Scene",
+                );
+                Aabb::surrounding_box(left_box, right_box)
+            }
+        };
 
         Self {
             left,
             right,
-            bounding_box: Aabb::surrounding_box(left_box, right_box),
+            bounding_box,
         }
     }
 
-    fn box_x_compare(obj1: &T, obj2: &T) -> Ordering {
-        if let (Some(bbox_a), Some(bbox_b)) =
-            (obj1.bounding_box(0.0, 0.0), obj2.bounding_box(0.0, 0.0))
+    fn pick_comparator(rng: &mut SmallRng) -> Comparator<T> {
+        *[
+            Self::box_x_compare as Comparator<T>,
+            Self::box_y_compare,
+            Self::box_z_compare,
+        ]
+        .choose(rng)
+        .unwrap()
+    }
+
+    /// Every subtree gets its own rng seeded from the parent. This keeps the
+    /// sequential and parallel builders drawing the same random numbers for
+    /// the same subtree, so both produce identical trees for the same seed.
+    fn split_rng(rng: &mut SmallRng) -> (SmallRng, SmallRng) {
+        (
+            SmallRng::seed_from_u64(rng.gen()),
+            SmallRng::seed_from_u64(rng.gen()),
+        )
+    }
+
+    fn box_x_compare(obj1: &T, obj2: &T, t0: f64, t1: f64) -> Ordering {
+        if let (Some(bbox_a), Some(bbox_b)) = (obj1.bounding_box(t0, t1), obj2.bounding_box(t0, t1))
         {
             return bbox_a.min.x().partial_cmp(&bbox_b.min.x()).unwrap();
         }
@@ -74,9 +122,8 @@ impl<T: Hitable + Clone> BvhNode<T> {
         panic!("No bounding box for this BVH Node!!")
     }
 
-    fn box_y_compare(obj1: &T, obj2: &T) -> Ordering {
-        if let (Some(bbox_a), Some(bbox_b)) =
-            (obj1.bounding_box(0.0, 0.0), obj2.bounding_box(0.0, 0.0))
+    fn box_y_compare(obj1: &T, obj2: &T, t0: f64, t1: f64) -> Ordering {
+        if let (Some(bbox_a), Some(bbox_b)) = (obj1.bounding_box(t0, t1), obj2.bounding_box(t0, t1))
         {
             return bbox_a.min.y().partial_cmp(&bbox_b.min.y()).unwrap();
         }
@@ -84,9 +131,8 @@ impl<T: Hitable + Clone> BvhNode<T> {
         panic!("No bounding box for this BVH Node!!")
     }
 
-    fn box_z_compare(obj1: &T, obj2: &T) -> Ordering {
-        if let (Some(bbox_a), Some(bbox_b)) =
-            (obj1.bounding_box(0.0, 0.0), obj2.bounding_box(0.0, 0.0))
+    fn box_z_compare(obj1: &T, obj2: &T, t0: f64, t1: f64) -> Ordering {
+        if let (Some(bbox_a), Some(bbox_b)) = (obj1.bounding_box(t0, t1), obj2.bounding_box(t0, t1))
         {
             return bbox_a.min.z().partial_cmp(&bbox_b.min.z()).unwrap();
         }
@@ -95,8 +141,69 @@ impl<T: Hitable + Clone> BvhNode<T> {
     }
 }
 
+impl<T: Hitable + Clone + Send + Sync> BvhNode<T> {
+    /// Same as [`BvhNode::new`] but sorts and recurses into both halves on the
+    /// rayon thread pool. For the same rng state it builds the exact same tree
+    /// as the sequential builder.
+    ///
+    /// Panics in the same cases as [`BvhNode::new`]
+    pub fn new_parallel<R: Rng + ?Sized>(rng: &mut R, objects: &mut [T], t0: f64, t1: f64) -> Self {
+        let mut rng = SmallRng::from_rng(rng).expect("error in seeding BVH builder");
+
+        Self::build_parallel(&mut rng, objects, t0, t1)
+    }
+
+    fn build_parallel(rng: &mut SmallRng, objects: &mut [T], t0: f64, t1: f64) -> Self {
+        let n = objects.len();
+        if n < PARALLEL_BUILD_THRESHOLD {
+            return Self::build(rng, objects, t0, t1);
+        }
+
+        let comparator = Self::pick_comparator(rng);
+        let (mut left_rng, mut right_rng) = Self::split_rng(rng);
+
+        objects.par_sort_by(|a, b| comparator(a, b, t0, t1));
+        let (l, r) = objects.split_at_mut(n / 2);
+
+        let (left, right) = rayon::join(
+            || Self::build_parallel(&mut left_rng, l, t0, t1),
+            || Self::build_parallel(&mut right_rng, r, t0, t1),
+        );
+
+        Self::from_children(
+            HitNode::Bvh(Box::new(left)),
+            HitNode::Bvh(Box::new(right)),
+            t0,
+            t1,
+        )
+    }
+}
+
+impl<T: Hitable> BvhNode<T> {
+    pub(crate) fn into_children(self) -> (HitNode<T>, HitNode<T>) {
+        (self.left, self.right)
+    }
+
+    fn collect_stats(&self, stats: &mut BuildStats, depth: usize, root_area: f64) {
+        let children = [&self.left, &self.right];
+        let leaf_size = children
+            .iter()
+            .filter(|child| matches!(child, HitNode::Direct(_)))
+            .count();
+
+        stats.add_node(depth, leaf_size, self.bounding_box.area() / root_area);
+
+        for child in children {
+            if let HitNode::Bvh(node) = child {
+                node.collect_stats(stats, depth + 1, root_area);
+            }
+        }
+    }
+}
+
 impl<T: Hitable> Hitable for BvhNode<T> {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        stats::count_box_tests(1);
         if !self.bounding_box.hit(ray, t_min, t_max) {
             return None;
         }
@@ -117,23 +224,95 @@ impl<T: Hitable> Hitable for BvhNode<T> {
     }
 }
 
-enum HitNode<T: Hitable> {
+impl<T: Hitable> Accelerator for BvhNode<T> {
+    fn stats(&self) -> BuildStats {
+        let mut stats = BuildStats::default();
+        self.collect_stats(&mut stats, 1, self.bounding_box.area().max(f64::EPSILON));
+        stats
+    }
+}
+
+pub(crate) enum HitNode<T: Hitable> {
     Bvh(Box<BvhNode<T>>),
     Direct(T),
+    /// Right side of a node built from a single object
+    Empty,
 }
 
 impl<T: Hitable> HitNode<T> {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
         match self {
             HitNode::Bvh(node) => node.hit(ray, t_min, t_max),
-            HitNode::Direct(node) => node.hit(ray, t_min, t_max),
+            HitNode::Direct(node) => {
+                stats::count_primitive_test();
+                node.hit(ray, t_min, t_max)
+            }
+            HitNode::Empty => None,
         }
     }
 
-    fn bounding_box(&self, t0: f64, t1: f64) -> Option<Aabb> {
+    pub(crate) fn bounding_box(&self, t0: f64, t1: f64) -> Option<Aabb> {
         match self {
             HitNode::Bvh(node) => node.bounding_box(t0, t1),
             HitNode::Direct(node) => node.bounding_box(t0, t1),
+            HitNode::Empty => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{prelude::SmallRng, Rng, SeedableRng};
+
+    use super::{BvhNode, HitNode, PARALLEL_BUILD_THRESHOLD};
+    use crate::{
+        hitable::{shapes::Sphere, Hitable},
+        materials::Lambertian,
+        texture::Solid,
+        types::Vec3,
+    };
+
+    /// Bounding boxes of every node, depth first, to compare the shape of trees
+    fn boxes<T: Hitable>(node: &BvhNode<T>, out: &mut Vec<String>) {
+        out.push(format!("{:?}", node.bounding_box));
+        for child in [&node.left, &node.right] {
+            if let HitNode::Bvh(child) = child {
+                boxes(child, out);
+            }
         }
     }
+
+    #[test]
+    fn parallel_build_matches_sequential() {
+        let mut rng = SmallRng::seed_from_u64(7);
+        let material = Lambertian::new(Solid::new(Vec3::splat(0.5)));
+        let objects: Vec<_> = (0..PARALLEL_BUILD_THRESHOLD * 3)
+            .map(|_| {
+                let center = Vec3::new(
+                    rng.gen_range(-100.0..100.0),
+                    rng.gen_range(-100.0..100.0),
+                    rng.gen_range(-100.0..100.0),
+                );
+                Sphere::new(center, rng.gen_range(0.1..2.0), material.clone())
+            })
+            .collect();
+
+        let sequential = BvhNode::new(
+            &mut SmallRng::seed_from_u64(42),
+            &mut objects.clone(),
+            0.0,
+            1.0,
+        );
+        let parallel = BvhNode::new_parallel(
+            &mut SmallRng::seed_from_u64(42),
+            &mut objects.clone(),
+            0.0,
+            1.0,
+        );
+
+        let (mut expected, mut actual) = (Vec::new(), Vec::new());
+        boxes(&sequential, &mut expected);
+        boxes(&parallel, &mut actual);
+        assert_eq!(expected, actual);
+    }
 }
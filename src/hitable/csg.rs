@@ -0,0 +1,148 @@
+use crate::{
+    hitable::{HitInterval, HitRecord, Hitable},
+    types::{Ray, Vec3},
+    Aabb,
+};
+
+#[derive(Debug, Copy, Clone)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl Operation {
+    fn inside(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            Operation::Union => in_a || in_b,
+            Operation::Intersection => in_a && in_b,
+            Operation::Difference => in_a && !in_b,
+        }
+    }
+}
+
+/// Constructive solid geometry, combines the volumes of two closed objects.
+///
+/// Works on the intervals the ray spends inside each object, so any `Hitable`
+/// can be used and nodes can be nested. Surfaces keep the material of the object
+/// they come from, e.g. the hole cut by a difference has the material of `b`
+pub struct Csg<A, B> {
+    a: A,
+    b: B,
+    operation: Operation,
+}
+
+impl<A: Hitable, B: Hitable> Csg<A, B> {
+    /// Everything inside either `a` or `b`
+    pub fn union(a: A, b: B) -> Self {
+        Self {
+            a,
+            b,
+            operation: Operation::Union,
+        }
+    }
+
+    /// Only the parts inside both `a` and `b`
+    pub fn intersection(a: A, b: B) -> Self {
+        Self {
+            a,
+            b,
+            operation: Operation::Intersection,
+        }
+    }
+
+    /// `a` with `b` carved out of it
+    pub fn difference(a: A, b: B) -> Self {
+        Self {
+            a,
+            b,
+            operation: Operation::Difference,
+        }
+    }
+}
+
+impl<A: Hitable, B: Hitable> Hitable for Csg<A, B> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let first = self.hit_intervals(ray, t_min, t_max).into_iter().next()?;
+
+        // Ray starting inside hits the way out first
+        first.enter.or(first.exit)
+    }
+
+    fn bounding_box(&self, t0: f64, t1: f64) -> Option<Aabb> {
+        let a = self.a.bounding_box(t0, t1)?;
+
+        match self.operation {
+            Operation::Union => {
+                let b = self.b.bounding_box(t0, t1)?;
+                Some(Aabb::surrounding_box(a, b))
+            }
+            Operation::Intersection => {
+                let b = self.b.bounding_box(t0, t1)?;
+                let min = Vec3::max(a.min, b.min);
+                // Disjoint objects leave an empty box, keep it well formed
+                let max = Vec3::max(Vec3::min(a.max, b.max), min);
+                Some(Aabb::new(min, max))
+            }
+            Operation::Difference => Some(a),
+        }
+    }
+
+    fn hit_intervals(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitInterval> {
+        let a = self.a.hit_intervals(ray, t_min, t_max);
+        let b = self.b.hit_intervals(ray, t_min, t_max);
+
+        // Starts inside an object when its first interval has no entry
+        let mut in_a = a.first().is_some_and(|i| i.enter.is_none());
+        let mut in_b = b.first().is_some_and(|i| i.enter.is_none());
+
+        // Every surface crossing, with the object it belongs to and whether it enters it.
+        // A missing exit is at t_max and can never change the result
+        let mut crossings: Vec<(HitRecord, bool, bool)> =
+            Vec::with_capacity(2 * (a.len() + b.len()));
+        for (intervals, is_a) in [(a, true), (b, false)] {
+            for interval in intervals {
+                crossings.extend(interval.enter.map(|hit| (hit, is_a, true)));
+                crossings.extend(interval.exit.map(|hit| (hit, is_a, false)));
+            }
+        }
+        crossings.sort_by(|x, y| x.0.t.total_cmp(&y.0.t));
+
+        let mut inside = self.operation.inside(in_a, in_b);
+        let mut intervals = Vec::new();
+        let mut enter = None;
+
+        for (mut hit, is_a, entering) in crossings {
+            if is_a {
+                in_a = entering;
+            } else {
+                in_b = entering;
+            }
+
+            let now_inside = self.operation.inside(in_a, in_b);
+            if now_inside == inside {
+                continue;
+            }
+
+            // The normal already faces against the ray, only the side changes,
+            // e.g. leaving `b` in a difference is entering the result
+            hit.front_face = now_inside;
+            inside = now_inside;
+
+            if inside {
+                enter = Some(hit);
+            } else {
+                intervals.push(HitInterval {
+                    enter: enter.take(),
+                    exit: Some(hit),
+                });
+            }
+        }
+
+        if inside {
+            intervals.push(HitInterval { enter, exit: None });
+        }
+
+        intervals
+    }
+}
@@ -1,9 +1,11 @@
 use std::sync::Arc;
 
+use rand::{prelude::SmallRng, Rng};
+
 use crate::{
     demos::ParallelHit,
-    hitable::{HitRecord, Hitable},
-    types::Ray,
+    hitable::{stats, Accelerator, BuildStats, HitRecord, Hitable},
+    types::{Ray, Vec3},
     Aabb,
 };
 
@@ -16,6 +18,7 @@ impl Hitable for HitableList {
         let mut closest_so_far = t_max;
         let mut hit_rec: Option<HitRecord> = None;
         for obj in &self.list {
+            stats::count_primitive_test();
             if let Some(l_hit_rec) = obj.hit(ray, t_min, closest_so_far) {
                 closest_so_far = l_hit_rec.t;
                 hit_rec = Some(l_hit_rec);
@@ -45,6 +48,38 @@ impl Hitable for HitableList {
 
         output_box
     }
+
+    /// Every object is equally likely to be sampled
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        if self.list.is_empty() {
+            return 0.0;
+        }
+        let weight = 1.0 / self.list.len() as f64;
+
+        self.list
+            .iter()
+            .map(|obj| weight * obj.pdf_value(ray))
+            .sum()
+    }
+
+    /// Panics if the list is empty, there is nothing to send rays towards
+    fn random(&self, origin: Vec3, time: f64, rng: &mut SmallRng) -> Vec3 {
+        assert!(
+            !self.list.is_empty(),
+            "can't sample a direction towards an empty HitableList"
+        );
+        let i = rng.gen_range(0..self.list.len());
+        self.list[i].random(origin, time, rng)
+    }
+}
+
+impl Accelerator for HitableList {
+    fn stats(&self) -> BuildStats {
+        // A flat list is a single leaf holding every object
+        let mut stats = BuildStats::default();
+        stats.add_node(1, self.list.len(), 1.0);
+        stats
+    }
 }
 
 impl HitableList {
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use rand::prelude::SmallRng;
+
+use crate::{
+    hitable::{HitRecord, Hitable},
+    types::{Ray, Vec3},
+    Aabb,
+};
+
+/// A placement of a shared object in the world.
+///
+/// The object, usually a bottom level `BvhNode`, is behind an `Arc` so any
+/// number of instances can reference one copy of the geometry. Each instance
+/// only stores its own transform, which is applied to the object in the order
+/// scale, rotation about Y and then translation, regardless of the order the
+/// builder methods are called in.
+pub struct Instance<T: ?Sized> {
+    object: Arc<T>,
+    scale: Vec3,
+    sin_theta: f64,
+    cos_theta: f64,
+    offset: Vec3,
+}
+
+impl<T: Hitable + ?Sized> Instance<T> {
+    pub fn new(object: Arc<T>) -> Self {
+        Self {
+            object,
+            scale: Vec3::splat(1.0),
+            sin_theta: 0.0,
+            cos_theta: 1.0,
+            offset: Vec3::splat(0.0),
+        }
+    }
+
+    /// Scale along each axis. None of the components should be zero
+    pub fn scaled(mut self, scale: impl Into<Vec3>) -> Self {
+        self.scale = scale.into();
+        self
+    }
+
+    /// Rotation about the Y axis, same convention as `Hitable::rotate_y`
+    pub fn rotated_y(mut self, angle: f64) -> Self {
+        let radians = angle.to_radians();
+        self.sin_theta = radians.sin();
+        self.cos_theta = radians.cos();
+        self
+    }
+
+    pub fn translated(mut self, offset: impl Into<Vec3>) -> Self {
+        self.offset = offset.into();
+        self
+    }
+
+    /// Maps a direction from object space to world space, ignoring translation
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        let v = v * self.scale;
+        Vec3::new(
+            self.cos_theta * v.x() + self.sin_theta * v.z(),
+            v.y(),
+            -self.sin_theta * v.x() + self.cos_theta * v.z(),
+        )
+    }
+
+    /// Maps a direction from world space to object space, ignoring translation
+    fn to_object(&self, v: Vec3) -> Vec3 {
+        let v = Vec3::new(
+            self.cos_theta * v.x() - self.sin_theta * v.z(),
+            v.y(),
+            self.sin_theta * v.x() + self.cos_theta * v.z(),
+        );
+        v / self.scale
+    }
+}
+
+impl<T: ?Sized> Clone for Instance<T> {
+    fn clone(&self) -> Self {
+        Self {
+            object: self.object.clone(),
+            scale: self.scale,
+            sin_theta: self.sin_theta,
+            cos_theta: self.cos_theta,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<T: Hitable + ?Sized> Hitable for Instance<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // The direction is not normalized after the transform
+        // so t stays the same in both spaces
+        let object_ray = Ray::new(
+            self.to_object(ray.origin - self.offset),
+            self.to_object(ray.direction),
+            ray.time(),
+        );
+
+        let mut hit = self.object.hit(&object_ray, t_min, t_max)?;
+
+        hit.p = self.to_world(hit.p) + self.offset;
+
+        // Normals transform with the inverse transpose. Rotation is orthogonal
+        // so only the scale has to be inverted
+        let normal = hit.normal / self.scale;
+        hit.normal = Vec3::new(
+            self.cos_theta * normal.x() + self.sin_theta * normal.z(),
+            normal.y(),
+            -self.sin_theta * normal.x() + self.cos_theta * normal.z(),
+        )
+        .unit_vector();
+
+        // Tangents and bitangents lie on the surface and transform like any other direction
+        hit.tangent = hit.tangent.map(|tangent| self.to_world(tangent));
+        hit.bitangent = hit.bitangent.map(|bitangent| self.to_world(bitangent));
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self, t0: f64, t1: f64) -> Option<Aabb> {
+        let bbox = self.object.bounding_box(t0, t1)?;
+
+        let mut min = Vec3::splat(f64::MAX);
+        let mut max = Vec3::splat(f64::MIN);
+
+        for i in 0..2 {
+            let x = if i == 0 { bbox.min.x() } else { bbox.max.x() };
+            for j in 0..2 {
+                let y = if j == 0 { bbox.min.y() } else { bbox.max.y() };
+                for k in 0..2 {
+                    let z = if k == 0 { bbox.min.z() } else { bbox.max.z() };
+
+                    let corner = self.to_world(Vec3::new(x, y, z)) + self.offset;
+                    min = Vec3::min(min, corner);
+                    max = Vec3::max(max, corner);
+                }
+            }
+        }
+
+        Some(Aabb::new(min, max))
+    }
+
+    /// Scaling squeezes directions together, by |det| / |A w|^3 for a unit
+    /// direction w going into object space through A
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        let direction = self.to_object(ray.direction.unit_vector());
+        let object_ray = Ray::new(
+            self.to_object(ray.origin - self.offset),
+            direction,
+            ray.time(),
+        );
+        let determinant = 1.0 / (self.scale.x() * self.scale.y() * self.scale.z()).abs();
+
+        self.object.pdf_value(&object_ray) * determinant / direction.length().powi(3)
+    }
+
+    fn random(&self, origin: Vec3, time: f64, rng: &mut SmallRng) -> Vec3 {
+        let direction = self
+            .object
+            .random(self.to_object(origin - self.offset), time, rng);
+        self.to_world(direction)
+    }
+}
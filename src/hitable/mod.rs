@@ -1,19 +1,41 @@
+mod alpha_mask;
+mod animated_transform;
 pub mod bvh;
+mod csg;
 pub mod hitable_list;
+mod instance;
+mod motion_bvh;
+pub mod qbvh;
 mod rotate;
+mod scene;
+pub mod sdf;
 pub mod shapes;
+pub mod stats;
+mod transform;
 mod translate;
 pub mod volume;
 
+pub use alpha_mask::{AlphaMask, AlphaTest};
+pub use animated_transform::{AnimatedTransform, Keyframe};
 pub use bvh::*;
+pub use csg::Csg;
+pub use instance::Instance;
+pub use motion_bvh::MotionBvh;
+pub use qbvh::Qbvh;
+pub use scene::Scene;
+pub use stats::{Accelerator, BuildStats};
+pub use transform::Transform;
 pub use translate::*;
 
 use std::sync::Arc;
 
+use rand::prelude::SmallRng;
+
 use crate::{
     hitable::rotate::Rotate,
-    types::{Ray, Vec3},
-    Aabb, Material, X, Y, Z,
+    texture::Footprint,
+    types::{Mat4, Ray, Vec3},
+    Aabb, Material, Texture, X, Y, Z,
 };
 
 pub struct HitRecord<'a> {
@@ -41,6 +63,24 @@ pub struct HitRecord<'a> {
     pub v: f64,
 
     pub front_face: bool,
+
+    /// color interpolated from the vertices of a mesh that has vertex colors
+    pub color: Option<Vec3>,
+
+    /// how p changes with u, along the surface in the direction u increases.
+    /// Not normalized, it's as long as p moves for a change of 1 in u, which
+    /// is what texture filtering needs. Materials that only want the direction
+    /// normalize it. Set by shapes that have one, e.g. along a hair fiber
+    pub tangent: Option<Vec3>,
+
+    /// how p changes with v, scaled the same way as `tangent` and set along
+    /// with it. Together they're the frame normal and bump maps are applied in
+    pub bitangent: Option<Vec3>,
+
+    /// how u & v change from one pixel to the next, for filtering textures.
+    /// Only known for rays that carry differentials on shapes with tangents,
+    /// see `set_footprint`
+    pub footprint: Option<Footprint>,
 }
 
 impl<'a> HitRecord<'a> {
@@ -59,9 +99,52 @@ impl<'a> HitRecord<'a> {
             u,
             v,
             front_face: false,
+            color: None,
+            tangent: None,
+            bitangent: None,
+            footprint: None,
         }
     }
 
+    /// Works out `footprint` from where the differentials of `ray` cross the
+    /// plane tangent to the surface, and how far that is in u & v.
+    /// Matt Pharr, Wenzel Jakob & Greg Humphreys, Physically Based Rendering, 10.1.1
+    /// https://pbr-book.org/3ed-2018/Texture/Sampling_and_Antialiasing#FindingtheTextureSamplingRate
+    pub fn set_footprint(&mut self, ray: &Ray) {
+        self.footprint = self.footprint_of(ray);
+    }
+
+    fn footprint_of(&self, ray: &Ray) -> Option<Footprint> {
+        let differentials = ray.differentials.as_ref()?;
+        let (dpdu, dpdv) = (self.tangent?, self.bitangent?);
+
+        let dpdx = differentials.x.on_plane(self.p, self.normal)? - self.p;
+        let dpdy = differentials.y.on_plane(self.p, self.normal)? - self.p;
+
+        // Least squares fit of dpdx = dpdu dudx + dpdv dvdx, same for y
+        let (uu, uv, vv) = (dpdu.dot(&dpdu), dpdu.dot(&dpdv), dpdv.dot(&dpdv));
+        let determinant = uu * vv - uv * uv;
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+        let solve = |d: Vec3| {
+            let (pu, pv) = (dpdu.dot(&d), dpdv.dot(&d));
+            (
+                (vv * pu - uv * pv) / determinant,
+                (uu * pv - uv * pu) / determinant,
+            )
+        };
+
+        let (dudx, dvdx) = solve(dpdx);
+        let (dudy, dvdy) = solve(dpdy);
+        Some(Footprint {
+            dudx,
+            dvdx,
+            dudy,
+            dvdy,
+        })
+    }
+
     pub fn set_face_normal(&mut self, ray: &Ray) {
         self.front_face = ray.direction.dot(&self.normal) < 0.0;
 
@@ -73,11 +156,79 @@ impl<'a> HitRecord<'a> {
     }
 }
 
+/// Limits how many surfaces the default `Hitable::hit_intervals` walks through
+const MAX_INTERVAL_HITS: usize = 64;
+
+/// Distance to step past a surface before looking for the next one
+const INTERVAL_EPSILON: f64 = 1e-6;
+
+/// A stretch of a ray that is inside an object
+pub struct HitInterval<'a> {
+    /// Where the ray goes into the object, `None` if it's already inside at `t_min`
+    pub enter: Option<HitRecord<'a>>,
+    /// Where the ray comes out of the object, `None` if it's still inside at `t_max`
+    pub exit: Option<HitRecord<'a>>,
+}
+
 pub trait Hitable {
     fn hit(&self, _ray: &Ray, _t_min: f64, _t_max: f64) -> Option<HitRecord>;
 
     fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb>;
 
+    /// Probability density, over solid angle seen from the origin of `ray`, of
+    /// `random` picking its direction. Objects that can't be sampled return 0
+    fn pdf_value(&self, _ray: &Ray) -> f64 {
+        0.0
+    }
+
+    /// A random direction from `origin` towards a point on the object at `time`,
+    /// used to send rays towards lights
+    fn random(&self, _origin: Vec3, _time: f64, _rng: &mut SmallRng) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+
+    /// Every stretch of the ray between `t_min` and `t_max` that is inside the object,
+    /// in order along the ray. Only makes sense for closed objects.
+    ///
+    /// The default walks through the surfaces with `hit` from far behind the origin
+    /// and counts crossings, so it doesn't depend on which way the normals point.
+    /// `front_face` of the records is set to whether the ray is entering
+    fn hit_intervals(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitInterval> {
+        let mut intervals = Vec::new();
+        let mut enter = None;
+        let mut inside = false;
+        let mut t = f64::MIN;
+
+        for _ in 0..MAX_INTERVAL_HITS {
+            let mut hit = match self.hit(ray, t, t_max) {
+                Some(v) => v,
+                None => break,
+            };
+
+            t = hit.t + INTERVAL_EPSILON;
+            inside = !inside;
+            hit.front_face = inside;
+
+            if inside {
+                enter = Some(hit);
+            } else if hit.t > t_min {
+                intervals.push(HitInterval {
+                    enter: enter.take().filter(|e: &HitRecord| e.t > t_min),
+                    exit: Some(hit),
+                });
+            }
+        }
+
+        if inside {
+            intervals.push(HitInterval {
+                enter: enter.filter(|e| e.t > t_min),
+                exit: None,
+            });
+        }
+
+        intervals
+    }
+
     fn translate(self, offset: impl Into<Vec3>) -> Translate<Self>
     where
         Self: Sized,
@@ -105,6 +256,46 @@ pub trait Hitable {
     {
         Rotate::new(self, angle)
     }
+
+    /// Places the object with an arbitrary affine transform
+    fn transform(self, matrix: Mat4) -> Transform<Self>
+    where
+        Self: Sized,
+    {
+        Transform::new(self, matrix)
+    }
+
+    /// Scale along each axis, around the origin
+    fn scale(self, factor: impl Into<Vec3>) -> Transform<Self>
+    where
+        Self: Sized,
+    {
+        Transform::new(self, Mat4::scaling(factor.into()))
+    }
+
+    /// Rotation by `angle` degrees around `axis`, which goes through the origin
+    fn rotate_axis(self, axis: impl Into<Vec3>, angle: f64) -> Transform<Self>
+    where
+        Self: Sized,
+    {
+        Transform::new(self, Mat4::rotation(axis.into(), angle))
+    }
+
+    /// Moves the object through `keyframes` over time, see `AnimatedTransform`
+    fn animate(self, keyframes: Vec<Keyframe>) -> AnimatedTransform<Self>
+    where
+        Self: Sized,
+    {
+        AnimatedTransform::new(self, keyframes)
+    }
+
+    /// Cuts holes where `alpha` is low, see `AlphaMask`
+    fn alpha_mask<A: Texture>(self, alpha: A, test: AlphaTest) -> AlphaMask<Self, A>
+    where
+        Self: Sized,
+    {
+        AlphaMask::new(self, alpha, test)
+    }
 }
 
 impl<T: Hitable + ?Sized> Hitable for Arc<T> {
@@ -114,4 +305,13 @@ impl<T: Hitable + ?Sized> Hitable for Arc<T> {
     fn bounding_box(&self, t0: f64, t1: f64) -> Option<Aabb> {
         self.as_ref().bounding_box(t0, t1)
     }
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        self.as_ref().pdf_value(ray)
+    }
+    fn random(&self, origin: Vec3, time: f64, rng: &mut SmallRng) -> Vec3 {
+        self.as_ref().random(origin, time, rng)
+    }
+    fn hit_intervals(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitInterval> {
+        self.as_ref().hit_intervals(ray, t_min, t_max)
+    }
 }
@@ -0,0 +1,215 @@
+use std::cmp::Ordering;
+
+use rand::Rng;
+
+use crate::{
+    hitable::{stats, Accelerator, BuildStats, HitRecord, Hitable},
+    types::{Ray, Vec3},
+    Aabb,
+};
+
+/// A BVH for fast moving objects.
+///
+/// A single box swept over the whole shutter interval is huge for an object
+/// that travels far, so most rays that test it miss the object. Instead,
+/// every node stores its bounds at `segments + 1` evenly spaced times between
+/// `t0` and `t1` and a ray is tested against the box interpolated to its own
+/// time. Objects are assumed to move linearly within a segment, which is
+/// exact for `MovingSphere`. Add segments for anything following a curve.
+pub struct MotionBvh<T: Hitable> {
+    root: MotionNode<T>,
+    t0: f64,
+    t1: f64,
+    segments: usize,
+}
+
+enum MotionNode<T: Hitable> {
+    Inner {
+        boxes: Box<[Aabb]>,
+        left: Box<MotionNode<T>>,
+        right: Box<MotionNode<T>>,
+    },
+    Leaf {
+        boxes: Box<[Aabb]>,
+        object: T,
+    },
+}
+
+impl<T: Hitable + Clone> MotionBvh<T> {
+    pub fn new<R: Rng + ?Sized>(
+        rng: &mut R,
+        objects: &mut [T],
+        t0: f64,
+        t1: f64,
+        segments: usize,
+    ) -> Self {
+        assert!(segments > 0, "motion BVH needs at least one time segment");
+        assert!(!objects.is_empty(), "motion BVH needs at least one object");
+
+        let times = (0..=segments)
+            .map(|i| t0 + (t1 - t0) * i as f64 / segments as f64)
+            .collect::<Vec<_>>();
+
+        Self {
+            root: MotionNode::build(rng, objects, &times),
+            t0,
+            t1,
+            segments,
+        }
+    }
+}
+
+impl<T: Hitable + Clone> MotionNode<T> {
+    fn build<R: Rng + ?Sized>(rng: &mut R, objects: &mut [T], times: &[f64]) -> Self {
+        if let [object] = objects {
+            let boxes = times
+                .iter()
+                .map(|&t| {
+                    object
+                        .bounding_box(t, t)
+                        .expect("missing bounding box for motion BVH leaf")
+                })
+                .collect();
+
+            return MotionNode::Leaf {
+                boxes,
+                object: object.clone(),
+            };
+        }
+
+        // Split along a random axis by where objects are half way through the interval
+        let axis = rng.gen_range(0..3);
+        let t_mid = (times[0] + times[times.len() - 1]) / 2.0;
+        objects.sort_by(|a, b| {
+            let a = Self::centroid(a, t_mid, axis);
+            let b = Self::centroid(b, t_mid, axis);
+            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+        });
+
+        let n = objects.len();
+        let (l, r) = objects.split_at_mut(n / 2);
+        let left = Self::build(rng, l, times);
+        let right = Self::build(rng, r, times);
+
+        let boxes = left
+            .boxes()
+            .iter()
+            .zip(right.boxes().iter())
+            .map(|(&a, &b)| Aabb::surrounding_box(a, b))
+            .collect();
+
+        MotionNode::Inner {
+            boxes,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    fn centroid(object: &T, t: f64, axis: usize) -> f64 {
+        let bbox = object
+            .bounding_box(t, t)
+            .expect("missing bounding box for motion BVH leaf");
+        let center = (bbox.min + bbox.max) / 2.0;
+
+        match axis {
+            0 => center.x(),
+            1 => center.y(),
+            _ => center.z(),
+        }
+    }
+}
+
+impl<T: Hitable> MotionNode<T> {
+    fn boxes(&self) -> &[Aabb] {
+        match self {
+            MotionNode::Inner { boxes, .. } | MotionNode::Leaf { boxes, .. } => boxes,
+        }
+    }
+
+    /// `segment` is the index of the key box at the start of the ray's segment
+    /// and `f` is how far along that segment the ray's time lies
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, segment: usize, f: f64) -> Option<HitRecord> {
+        let boxes = self.boxes();
+        let (start, end) = (boxes[segment], boxes[segment + 1]);
+        let bbox = Aabb::new(lerp(start.min, end.min, f), lerp(start.max, end.max, f));
+
+        stats::count_box_tests(1);
+        if !bbox.hit(ray, t_min, t_max) {
+            return None;
+        }
+
+        match self {
+            MotionNode::Leaf { object, .. } => {
+                stats::count_primitive_test();
+                object.hit(ray, t_min, t_max)
+            }
+            MotionNode::Inner { left, right, .. } => {
+                let hit_left = left.hit(ray, t_min, t_max, segment, f);
+                let closest = hit_left.as_ref().map_or(t_max, |hit| hit.t);
+                let hit_right = right.hit(ray, t_min, closest, segment, f);
+
+                hit_right.or(hit_left)
+            }
+        }
+    }
+
+    fn collect_stats(&self, stats: &mut BuildStats, depth: usize, root_area: f64) {
+        match self {
+            MotionNode::Leaf { .. } => (),
+            MotionNode::Inner { boxes, left, right } => {
+                let leaf_size = [left, right]
+                    .into_iter()
+                    .filter(|child| matches!(***child, MotionNode::Leaf { .. }))
+                    .count();
+
+                stats.add_node(depth, leaf_size, swept_box(boxes).area() / root_area);
+                left.collect_stats(stats, depth + 1, root_area);
+                right.collect_stats(stats, depth + 1, root_area);
+            }
+        }
+    }
+}
+
+fn swept_box(boxes: &[Aabb]) -> Aabb {
+    boxes
+        .iter()
+        .copied()
+        .reduce(Aabb::surrounding_box)
+        .expect("motion BVH node without any boxes")
+}
+
+impl<T: Hitable> Hitable for MotionBvh<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let position = if self.t1 > self.t0 {
+            ((ray.time() - self.t0) / (self.t1 - self.t0)).clamp(0.0, 1.0) * self.segments as f64
+        } else {
+            0.0
+        };
+        let segment = (position as usize).min(self.segments - 1);
+
+        self.root
+            .hit(ray, t_min, t_max, segment, position - segment as f64)
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        Some(swept_box(self.root.boxes()))
+    }
+}
+
+impl<T: Hitable> Accelerator for MotionBvh<T> {
+    fn stats(&self) -> BuildStats {
+        let mut stats = BuildStats::default();
+        let root_area = swept_box(self.root.boxes()).area().max(f64::EPSILON);
+
+        match self.root {
+            MotionNode::Leaf { .. } => stats.add_node(1, 1, 1.0),
+            _ => self.root.collect_stats(&mut stats, 1, root_area),
+        }
+
+        stats
+    }
+}
+
+fn lerp(a: Vec3, b: Vec3, f: f64) -> Vec3 {
+    a * (1.0 - f) + b * f
+}
@@ -0,0 +1,63 @@
+use crate::{types::Vec3, Aabb};
+
+/// Four bounding boxes stored as structure of arrays so all of them can be
+/// tested against a ray at once. Portable fallback for `simd_aabb4`.
+#[derive(Debug, Copy, Clone)]
+pub struct Aabb4 {
+    min: [[f64; 4]; 3],
+    max: [[f64; 4]; 3],
+    valid: [bool; 4],
+}
+
+impl Aabb4 {
+    /// Lanes past the end of `boxes` are never hit
+    pub fn new(boxes: &[Aabb]) -> Self {
+        assert!(boxes.len() <= 4, "Aabb4 can only hold 4 boxes");
+
+        let mut min = [[0.0; 4]; 3];
+        let mut max = [[0.0; 4]; 3];
+        let mut valid = [false; 4];
+
+        for (lane, bbox) in boxes.iter().enumerate() {
+            min[0][lane] = bbox.min.x();
+            min[1][lane] = bbox.min.y();
+            min[2][lane] = bbox.min.z();
+            max[0][lane] = bbox.max.x();
+            max[1][lane] = bbox.max.y();
+            max[2][lane] = bbox.max.z();
+            valid[lane] = true;
+        }
+
+        Self { min, max, valid }
+    }
+
+    /// Returns a bitmask of the boxes hit by the ray along with
+    /// the distance at which the ray enters each of them
+    pub fn hit(&self, origin: Vec3, inv_direction: Vec3, t_min: f64, t_max: f64) -> (u8, [f64; 4]) {
+        let origin = [origin.x(), origin.y(), origin.z()];
+        let inv_direction = [inv_direction.x(), inv_direction.y(), inv_direction.z()];
+
+        let mut mask = 0;
+        let mut t_near = [0.0; 4];
+
+        for (lane, near) in t_near.iter_mut().enumerate() {
+            let mut tmin = t_min;
+            let mut tmax = t_max;
+
+            for axis in 0..3 {
+                let t0 = (self.min[axis][lane] - origin[axis]) * inv_direction[axis];
+                let t1 = (self.max[axis][lane] - origin[axis]) * inv_direction[axis];
+
+                tmin = tmin.max(t0.min(t1));
+                tmax = tmax.min(t0.max(t1));
+            }
+
+            if self.valid[lane] && tmax > tmin {
+                mask |= 1 << lane;
+            }
+            *near = tmin;
+        }
+
+        (mask, t_near)
+    }
+}
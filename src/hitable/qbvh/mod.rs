@@ -0,0 +1,219 @@
+#[cfg(not(target_arch = "x86_64"))]
+mod aabb4;
+#[cfg(not(target_arch = "x86_64"))]
+pub use aabb4::Aabb4;
+
+#[cfg(target_arch = "x86_64")]
+mod simd_aabb4;
+#[cfg(target_arch = "x86_64")]
+pub use simd_aabb4::Aabb4;
+
+use std::cmp::Ordering;
+
+use crate::{
+    hitable::{bvh::HitNode, stats, Accelerator, BuildStats, BvhNode, HitRecord, Hitable},
+    types::{Ray, Vec3},
+    Aabb,
+};
+
+/// Traversal stack size. A collapsed tree is half as deep as the binary tree
+/// it came from and every level pushes at most 4 entries, which is plenty
+/// for the median split trees built by `BvhNode`.
+const STACK_SIZE: usize = 64;
+
+/// A 4-ary BVH. Each node holds the boxes of its four children in
+/// structure of arrays form so they can be tested against a ray together.
+///
+/// It is built by collapsing a binary `BvhNode`, pulling grandchildren up
+/// into their parent until every node has four children.
+pub struct Qbvh<T: Hitable> {
+    nodes: Vec<QbvhNode>,
+    objects: Vec<T>,
+    bounding_box: Aabb,
+}
+
+struct QbvhNode {
+    bounds: Aabb4,
+    children: [Child; 4],
+    // Surface area of the union of the child boxes
+    area: f64,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Child {
+    Empty,
+    Node(u32),
+    Object(u32),
+}
+
+impl<T: Hitable> Qbvh<T> {
+    pub fn from_bvh(bvh: BvhNode<T>, t0: f64, t1: f64) -> Self {
+        let mut qbvh = Self {
+            nodes: Vec::new(),
+            objects: Vec::new(),
+            bounding_box: bvh
+                .bounding_box(t0, t1)
+                .expect("missing bounding box for BVH Node"),
+        };
+
+        qbvh.collapse(bvh, t0, t1);
+        qbvh
+    }
+
+    fn collapse(&mut self, node: BvhNode<T>, t0: f64, t1: f64) -> u32 {
+        let index = self.nodes.len();
+        self.nodes.push(QbvhNode {
+            bounds: Aabb4::new(&[]),
+            children: [Child::Empty; 4],
+            area: 0.0,
+        });
+
+        let (left, right) = node.into_children();
+        let mut children = vec![left, right];
+        children.retain(|child| !matches!(child, HitNode::Empty));
+
+        // Open up the inner child with the largest surface area until
+        // there are four children or only objects are left
+        while children.len() < 4 {
+            let largest = children
+                .iter()
+                .enumerate()
+                .filter(|(_, child)| matches!(child, HitNode::Bvh(_)))
+                .map(|(i, child)| (i, child.bounding_box(t0, t1).map_or(0.0, |b| b.area())))
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+            let Some((i, _)) = largest else {
+                break;
+            };
+
+            if let HitNode::Bvh(inner) = children.swap_remove(i) {
+                let (left, right) = inner.into_children();
+                children.push(left);
+                if !matches!(right, HitNode::Empty) {
+                    children.push(right);
+                }
+            }
+        }
+
+        let boxes = children
+            .iter()
+            .map(|child| {
+                child
+                    .bounding_box(t0, t1)
+                    .expect("missing bounding box for QBVH child")
+            })
+            .collect::<Vec<_>>();
+
+        let mut slots = [Child::Empty; 4];
+        for (slot, child) in slots.iter_mut().zip(children) {
+            *slot = match child {
+                HitNode::Bvh(inner) => Child::Node(self.collapse(*inner, t0, t1)),
+                HitNode::Direct(object) => {
+                    self.objects.push(object);
+                    Child::Object(self.objects.len() as u32 - 1)
+                }
+                HitNode::Empty => Child::Empty,
+            };
+        }
+
+        let area = boxes
+            .iter()
+            .copied()
+            .reduce(Aabb::surrounding_box)
+            .map_or(0.0, |bbox| bbox.area());
+
+        self.nodes[index] = QbvhNode {
+            bounds: Aabb4::new(&boxes),
+            children: slots,
+            area,
+        };
+
+        index as u32
+    }
+
+    fn collect_stats(&self, stats: &mut BuildStats, index: u32, depth: usize, root_area: f64) {
+        let node = &self.nodes[index as usize];
+        let leaf_size = node
+            .children
+            .iter()
+            .filter(|child| matches!(child, Child::Object(_)))
+            .count();
+
+        stats.add_node(depth, leaf_size, node.area / root_area);
+
+        for child in node.children {
+            if let Child::Node(i) = child {
+                self.collect_stats(stats, i, depth + 1, root_area);
+            }
+        }
+    }
+}
+
+impl<T: Hitable> Accelerator for Qbvh<T> {
+    fn stats(&self) -> BuildStats {
+        let mut stats = BuildStats::default();
+        self.collect_stats(&mut stats, 0, 1, self.bounding_box.area().max(f64::EPSILON));
+        stats
+    }
+}
+
+impl<T: Hitable> Hitable for Qbvh<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let inv_direction = Vec3::splat(1.0) / ray.direction;
+
+        let mut closest_so_far = t_max;
+        let mut hit_rec = None;
+
+        // Each entry carries the distance at which the ray enters it so
+        // entries behind the closest hit found so far can be skipped
+        let mut stack = [(Child::Empty, 0.0); STACK_SIZE];
+        stack[0] = (Child::Node(0), t_min);
+        let mut len = 1;
+
+        while len > 0 {
+            len -= 1;
+            let (child, t_near) = stack[len];
+
+            if t_near > closest_so_far {
+                continue;
+            }
+
+            match child {
+                Child::Empty => (),
+                Child::Object(i) => {
+                    stats::count_primitive_test();
+                    if let Some(rec) = self.objects[i as usize].hit(ray, t_min, closest_so_far) {
+                        closest_so_far = rec.t;
+                        hit_rec = Some(rec);
+                    }
+                }
+                Child::Node(i) => {
+                    let node = &self.nodes[i as usize];
+                    stats::count_box_tests(4);
+                    let (mask, t_near) =
+                        node.bounds
+                            .hit(ray.origin, inv_direction, t_min, closest_so_far);
+
+                    // Push the farthest child first so the nearest one is visited next
+                    let mut order = [0, 1, 2, 3];
+                    order.sort_unstable_by(|&a, &b| {
+                        t_near[b].partial_cmp(&t_near[a]).unwrap_or(Ordering::Equal)
+                    });
+
+                    for lane in order {
+                        if mask & (1 << lane) != 0 {
+                            stack[len] = (node.children[lane], t_near[lane]);
+                            len += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        hit_rec
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        Some(self.bounding_box)
+    }
+}
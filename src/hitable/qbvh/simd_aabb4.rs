@@ -0,0 +1,84 @@
+use packed_simd::{f64x4, m64x4};
+
+use crate::{types::Vec3, Aabb};
+
+/// Four bounding boxes stored as structure of arrays so all of them can be
+/// tested against a ray in a single SIMD operation per slab.
+#[derive(Debug, Copy, Clone)]
+pub struct Aabb4 {
+    min_x: f64x4,
+    min_y: f64x4,
+    min_z: f64x4,
+    max_x: f64x4,
+    max_y: f64x4,
+    max_z: f64x4,
+    valid: m64x4,
+}
+
+impl Aabb4 {
+    /// Lanes past the end of `boxes` are never hit
+    pub fn new(boxes: &[Aabb]) -> Self {
+        assert!(boxes.len() <= 4, "Aabb4 can only hold 4 boxes");
+
+        let mut min = [[0.0; 4]; 3];
+        let mut max = [[0.0; 4]; 3];
+        let mut valid = [false; 4];
+
+        for (lane, bbox) in boxes.iter().enumerate() {
+            min[0][lane] = bbox.min.x();
+            min[1][lane] = bbox.min.y();
+            min[2][lane] = bbox.min.z();
+            max[0][lane] = bbox.max.x();
+            max[1][lane] = bbox.max.y();
+            max[2][lane] = bbox.max.z();
+            valid[lane] = true;
+        }
+
+        Self {
+            min_x: f64x4::from_slice_unaligned(&min[0]),
+            min_y: f64x4::from_slice_unaligned(&min[1]),
+            min_z: f64x4::from_slice_unaligned(&min[2]),
+            max_x: f64x4::from_slice_unaligned(&max[0]),
+            max_y: f64x4::from_slice_unaligned(&max[1]),
+            max_z: f64x4::from_slice_unaligned(&max[2]),
+            valid: m64x4::new(valid[0], valid[1], valid[2], valid[3]),
+        }
+    }
+
+    /// Returns a bitmask of the boxes hit by the ray along with
+    /// the distance at which the ray enters each of them
+    pub fn hit(&self, origin: Vec3, inv_direction: Vec3, t_min: f64, t_max: f64) -> (u8, [f64; 4]) {
+        let (ox, oy, oz) = (
+            f64x4::splat(origin.x()),
+            f64x4::splat(origin.y()),
+            f64x4::splat(origin.z()),
+        );
+        let (ix, iy, iz) = (
+            f64x4::splat(inv_direction.x()),
+            f64x4::splat(inv_direction.y()),
+            f64x4::splat(inv_direction.z()),
+        );
+
+        let (tx0, tx1) = ((self.min_x - ox) * ix, (self.max_x - ox) * ix);
+        let (ty0, ty1) = ((self.min_y - oy) * iy, (self.max_y - oy) * iy);
+        let (tz0, tz1) = ((self.min_z - oz) * iz, (self.max_z - oz) * iz);
+
+        let tmin = tx0
+            .min(tx1)
+            .max(ty0.min(ty1))
+            .max(tz0.min(tz1))
+            .max(f64x4::splat(t_min));
+        let tmax = tx0
+            .max(tx1)
+            .min(ty0.max(ty1))
+            .min(tz0.max(tz1))
+            .min(f64x4::splat(t_max));
+
+        let mask = (tmax.gt(tmin) & self.valid).bitmask();
+
+        let mut t_near = [0.0; 4];
+        tmin.write_to_slice_unaligned(&mut t_near);
+
+        (mask, t_near)
+    }
+}
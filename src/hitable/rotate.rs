@@ -1,8 +1,10 @@
 use std::marker::PhantomData;
 
+use rand::prelude::SmallRng;
+
 use crate::{
     hitable::{HitRecord, Hitable},
-    types::{Ray, Vec3},
+    types::{OffsetRay, Ray, Vec3},
     Aabb, Dimension,
 };
 
@@ -71,6 +73,16 @@ where
             _tag: PhantomData,
         }
     }
+
+    fn to_object(&self, v: Vec3) -> Vec3 {
+        v.set::<D2>(self.cos_theta * v.get::<D2>() - self.sin_theta * v.get::<D3>())
+            .set::<D3>(self.sin_theta * v.get::<D2>() + self.cos_theta * v.get::<D3>())
+    }
+
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        v.set::<D2>(self.cos_theta * v.get::<D2>() + self.sin_theta * v.get::<D3>())
+            .set::<D3>(-self.sin_theta * v.get::<D2>() + self.cos_theta * v.get::<D3>())
+    }
 }
 
 impl<D1, D2, D3, T> Hitable for Rotate<D1, D2, D3, T>
@@ -81,43 +93,24 @@ where
     T: Hitable,
 {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let origin = ray
-            .origin
-            .set::<D2>(
-                self.cos_theta * ray.origin.get::<D2>() - self.sin_theta * ray.origin.get::<D3>(),
-            )
-            .set::<D3>(
-                self.sin_theta * ray.origin.get::<D2>() + self.cos_theta * ray.origin.get::<D3>(),
-            );
-
-        let direction = ray
-            .direction
-            .set::<D2>(
-                self.cos_theta * ray.direction.get::<D2>()
-                    - self.sin_theta * ray.direction.get::<D3>(),
-            )
-            .set::<D3>(
-                self.sin_theta * ray.direction.get::<D2>()
-                    + self.cos_theta * ray.direction.get::<D3>(),
-            );
-
-        let rotated_ray = Ray::new(origin, direction, ray.time());
+        let rotated_ray = Ray::new(
+            self.to_object(ray.origin),
+            self.to_object(ray.direction),
+            ray.time(),
+        )
+        .with_differentials(ray.differentials.map(|differentials| {
+            differentials.map(|offset| OffsetRay {
+                origin: self.to_object(offset.origin),
+                direction: self.to_object(offset.direction),
+            })
+        }));
 
         let mut hit = self.hitable.hit(&rotated_ray, t_min, t_max)?;
 
-        hit.p = hit
-            .p
-            .set::<D2>(self.cos_theta * hit.p.get::<D2>() + self.sin_theta * hit.p.get::<D3>())
-            .set::<D3>(-self.sin_theta * hit.p.get::<D2>() + self.cos_theta * hit.p.get::<D3>());
-
-        hit.normal = hit
-            .normal
-            .set::<D2>(
-                self.cos_theta * hit.normal.get::<D2>() + self.sin_theta * hit.normal.get::<D3>(),
-            )
-            .set::<D3>(
-                -self.sin_theta * hit.normal.get::<D2>() + self.cos_theta * hit.normal.get::<D3>(),
-            );
+        hit.p = self.to_world(hit.p);
+        hit.normal = self.to_world(hit.normal);
+        hit.tangent = hit.tangent.map(|tangent| self.to_world(tangent));
+        hit.bitangent = hit.bitangent.map(|bitangent| self.to_world(bitangent));
 
         hit.set_face_normal(&rotated_ray);
 
@@ -127,4 +120,18 @@ where
     fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
         self.bbox
     }
+
+    // Rotations keep angles, so densities over solid angle are the same on both sides
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        let rotated_ray = Ray::new(
+            self.to_object(ray.origin),
+            self.to_object(ray.direction),
+            ray.time(),
+        );
+        self.hitable.pdf_value(&rotated_ray)
+    }
+
+    fn random(&self, origin: Vec3, time: f64, rng: &mut SmallRng) -> Vec3 {
+        self.to_world(self.hitable.random(self.to_object(origin), time, rng))
+    }
 }
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{
+    demos::ParallelHit,
+    hitable::{Accelerator, BuildStats, BvhNode, HitRecord, Hitable},
+    types::Ray,
+    Aabb,
+};
+
+/// The whole world of a demo, an accelerator over everything that has a
+/// bounding box plus a list of the objects that don't, like an `InfinitePlane`.
+///
+/// Unbounded objects are tested against every ray after the accelerator, they
+/// should be few and cheap to hit.
+pub struct Scene<T> {
+    bounded: Option<T>,
+    unbounded: Vec<Arc<dyn ParallelHit>>,
+}
+
+impl Scene<BvhNode<Arc<dyn ParallelHit>>> {
+    /// Sorts `objects` by whether they have a bounding box over `t0..t1` and
+    /// builds a `BvhNode` over the ones that do
+    pub fn new<R: Rng + ?Sized>(
+        rng: &mut R,
+        objects: Vec<Arc<dyn ParallelHit>>,
+        t0: f64,
+        t1: f64,
+    ) -> Self {
+        let (mut bounded, unbounded): (Vec<_>, Vec<_>) = objects
+            .into_iter()
+            .partition(|object| object.bounding_box(t0, t1).is_some());
+
+        Self {
+            bounded: (!bounded.is_empty()).then(|| BvhNode::new(rng, &mut bounded, t0, t1)),
+            unbounded,
+        }
+    }
+}
+
+impl<T: Hitable> Scene<T> {
+    /// Swaps the accelerator over the bounded objects, e.g. for a `Qbvh`
+    /// collapsed from the `BvhNode`
+    pub fn map<U: Hitable>(self, f: impl FnOnce(T) -> U) -> Scene<U> {
+        Scene {
+            bounded: self.bounded.map(f),
+            unbounded: self.unbounded,
+        }
+    }
+}
+
+impl<T: Hitable> Hitable for Scene<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut hit_rec = self
+            .bounded
+            .as_ref()
+            .and_then(|bounded| bounded.hit(ray, t_min, t_max));
+
+        for object in &self.unbounded {
+            let closest_so_far = hit_rec.as_ref().map_or(t_max, |hit| hit.t);
+            if let Some(hit) = object.hit(ray, t_min, closest_so_far) {
+                hit_rec = Some(hit);
+            }
+        }
+
+        hit_rec
+    }
+
+    /// `None` as soon as there is anything unbounded in the scene
+    fn bounding_box(&self, t0: f64, t1: f64) -> Option<Aabb> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+        self.bounded.as_ref()?.bounding_box(t0, t1)
+    }
+}
+
+impl<T: Accelerator> Accelerator for Scene<T> {
+    fn stats(&self) -> BuildStats {
+        self.bounded
+            .as_ref()
+            .map(Accelerator::stats)
+            .unwrap_or_default()
+    }
+}
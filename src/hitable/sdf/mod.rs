@@ -0,0 +1,178 @@
+mod operations;
+mod primitives;
+
+pub use operations::{Combine, Scale, Translate};
+pub use primitives::{Capsule, Cuboid, Mandelbulb, Sphere, Torus};
+
+use crate::{
+    hitable::{shapes, HitRecord, Hitable},
+    types::{Ray, Vec3},
+    Aabb, Material,
+};
+
+/// Limit on the steps taken by the sphere tracer before giving up on a ray
+const MAX_STEPS: usize = 128;
+
+/// Distance from the surface at which a ray counts as having hit it
+const SURFACE_EPSILON: f64 = 1e-5;
+
+/// Offset used to sample the field around a point when estimating the normal
+const NORMAL_EPSILON: f64 = 1e-6;
+
+/// A signed distance field, the distance from a point to the closest point on a
+/// surface, negative inside of it.
+///
+/// The distance can be an underestimate but never more than the real one,
+/// otherwise the sphere tracer might step over the surface
+pub trait Sdf {
+    fn distance(&self, p: Vec3) -> f64;
+
+    /// Everything inside either field
+    fn union<B: Sdf>(self, other: B) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::union(self, other, 0.0)
+    }
+
+    /// Only the parts inside both fields
+    fn intersection<B: Sdf>(self, other: B) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::intersection(self, other, 0.0)
+    }
+
+    /// This field with `other` carved out of it
+    fn difference<B: Sdf>(self, other: B) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::difference(self, other, 0.0)
+    }
+
+    /// Union that melts the surfaces together over a distance of about `smoothness`
+    fn smooth_union<B: Sdf>(self, other: B, smoothness: f64) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::union(self, other, smoothness)
+    }
+
+    /// Intersection with the edges rounded over a distance of about `smoothness`
+    fn smooth_intersection<B: Sdf>(self, other: B, smoothness: f64) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::intersection(self, other, smoothness)
+    }
+
+    /// Difference with the edges of the cut rounded over a distance of about `smoothness`
+    fn smooth_difference<B: Sdf>(self, other: B, smoothness: f64) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::difference(self, other, smoothness)
+    }
+
+    fn translate(self, offset: impl Into<Vec3>) -> Translate<Self>
+    where
+        Self: Sized,
+    {
+        Translate::new(self, offset.into())
+    }
+
+    fn scale(self, factor: f64) -> Scale<Self>
+    where
+        Self: Sized,
+    {
+        Scale::new(self, factor)
+    }
+}
+
+/// Renders a `Sdf` by sphere tracing it.
+///
+/// Fields can't tell how big they are, so the bounding box has to be given.
+/// It's used to place the object in a `BvhNode` and to clip rays before tracing.
+/// The normal is the gradient of the field and uv is mapped like on a `Sphere`
+/// from the normal
+pub struct SphereTraced<S, T> {
+    sdf: S,
+    bbox: Aabb,
+    material: T,
+}
+
+impl<S: Sdf, T: Material> SphereTraced<S, T> {
+    pub fn new(sdf: S, bbox: Aabb, material: T) -> Self {
+        Self {
+            sdf,
+            bbox,
+            material,
+        }
+    }
+
+    /// Estimate of the gradient from four samples on the corners of a tetrahedron
+    /// https://iquilezles.org/articles/normalsSDF/
+    fn normal(&self, p: Vec3) -> Vec3 {
+        [
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ]
+        .into_iter()
+        .fold(Vec3::splat(0.0), |acc, k| {
+            acc + k * self.sdf.distance(p + k * NORMAL_EPSILON)
+        })
+        .unit_vector()
+    }
+}
+
+impl<S: Sdf, T: Material> Hitable for SphereTraced<S, T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Only trace the part of the ray inside the bounding box
+        let t0 = (self.bbox.min - ray.origin) / ray.direction;
+        let t1 = (self.bbox.max - ray.origin) / ray.direction;
+        let mut t = t0.min(t1).max_element(t_min);
+        let t_end = t0.max(t1).min_element(t_max);
+
+        if t_end <= t {
+            return None;
+        }
+
+        // The field measures distances along a unit direction
+        let length = ray.direction.length();
+
+        // Rays scattered off the surface start right next to it and
+        // shouldn't hit it again before getting clear of it
+        let mut leaving_surface = true;
+
+        for _ in 0..MAX_STEPS {
+            let p = ray.point_at_parameter(t);
+
+            // Stepping by the absolute distance lets rays that start inside get out
+            let distance = self.sdf.distance(p).abs();
+            if distance >= SURFACE_EPSILON {
+                leaving_surface = false;
+            } else if !leaving_surface {
+                let normal = self.normal(p);
+                let mut hit_rec =
+                    HitRecord::new(t, p, normal, &self.material, shapes::sphere_uv(normal));
+                hit_rec.set_face_normal(ray);
+
+                return Some(hit_rec);
+            }
+
+            t += distance.max(SURFACE_EPSILON) / length;
+            if t >= t_end {
+                return None;
+            }
+        }
+
+        None
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{hitable::sdf::Sdf, types::Vec3};
+
+#[derive(Debug, Copy, Clone)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+/// Boolean operation on two fields, see `Sdf::union` and friends.
+///
+/// With a `smoothness` above 0 the sharp min/max is replaced with the polynomial
+/// smooth min, which blends the surfaces where they are closer than `smoothness`
+/// https://iquilezles.org/articles/smin/
+#[derive(Debug, Copy, Clone)]
+pub struct Combine<A, B> {
+    a: A,
+    b: B,
+    operation: Operation,
+    smoothness: f64,
+}
+
+impl<A: Sdf, B: Sdf> Combine<A, B> {
+    pub const fn union(a: A, b: B, smoothness: f64) -> Self {
+        Self {
+            a,
+            b,
+            operation: Operation::Union,
+            smoothness,
+        }
+    }
+
+    pub const fn intersection(a: A, b: B, smoothness: f64) -> Self {
+        Self {
+            a,
+            b,
+            operation: Operation::Intersection,
+            smoothness,
+        }
+    }
+
+    pub const fn difference(a: A, b: B, smoothness: f64) -> Self {
+        Self {
+            a,
+            b,
+            operation: Operation::Difference,
+            smoothness,
+        }
+    }
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Combine<A, B> {
+    fn distance(&self, p: Vec3) -> f64 {
+        let a = self.a.distance(p);
+        let b = self.b.distance(p);
+        let k = self.smoothness;
+
+        match self.operation {
+            Operation::Union => smooth_min(a, b, k),
+            Operation::Intersection => -smooth_min(-a, -b, k),
+            Operation::Difference => -smooth_min(-a, b, k),
+        }
+    }
+}
+
+fn smooth_min(a: f64, b: f64, k: f64) -> f64 {
+    if k <= 0.0 {
+        return a.min(b);
+    }
+
+    let h = (k - (a - b).abs()).max(0.0) / k;
+    a.min(b) - h * h * k * 0.25
+}
+
+/// Moves a field by `offset`
+#[derive(Debug, Copy, Clone)]
+pub struct Translate<S> {
+    sdf: S,
+    offset: Vec3,
+}
+
+impl<S: Sdf> Translate<S> {
+    pub const fn new(sdf: S, offset: Vec3) -> Self {
+        Self { sdf, offset }
+    }
+}
+
+impl<S: Sdf> Sdf for Translate<S> {
+    fn distance(&self, p: Vec3) -> f64 {
+        self.sdf.distance(p - self.offset)
+    }
+}
+
+/// Grows or shrinks a field around the origin by `factor` on every axis.
+/// Scaling each axis differently would break the distance so it isn't offered
+#[derive(Debug, Copy, Clone)]
+pub struct Scale<S> {
+    sdf: S,
+    factor: f64,
+}
+
+impl<S: Sdf> Scale<S> {
+    pub const fn new(sdf: S, factor: f64) -> Self {
+        Self { sdf, factor }
+    }
+}
+
+impl<S: Sdf> Sdf for Scale<S> {
+    fn distance(&self, p: Vec3) -> f64 {
+        self.sdf.distance(p / self.factor) * self.factor
+    }
+}
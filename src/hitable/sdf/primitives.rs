@@ -0,0 +1,148 @@
+use crate::{hitable::sdf::Sdf, types::Vec3};
+
+/// Ball of `radius` around the origin
+#[derive(Debug, Copy, Clone)]
+pub struct Sphere {
+    radius: f64,
+}
+
+impl Sphere {
+    pub const fn new(radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl Sdf for Sphere {
+    fn distance(&self, p: Vec3) -> f64 {
+        p.length() - self.radius
+    }
+}
+
+/// Box centered on the origin, reaching `half_extents` along each axis.
+/// A `radius` above 0 rounds the edges without growing the box
+#[derive(Debug, Copy, Clone)]
+pub struct Cuboid {
+    half_extents: Vec3,
+    radius: f64,
+}
+
+impl Cuboid {
+    pub const fn new(half_extents: Vec3) -> Self {
+        Self {
+            half_extents,
+            radius: 0.0,
+        }
+    }
+
+    pub const fn rounded(mut self, radius: f64) -> Self {
+        self.radius = radius;
+        self
+    }
+}
+
+impl Sdf for Cuboid {
+    fn distance(&self, p: Vec3) -> f64 {
+        let p = Vec3::new(p.x().abs(), p.y().abs(), p.z().abs());
+        let q = p - (self.half_extents - Vec3::splat(self.radius));
+
+        let outside = q.max(Vec3::splat(0.0)).length();
+        let inside = q.x().max(q.y()).max(q.z()).min(0.0);
+
+        outside + inside - self.radius
+    }
+}
+
+/// Ring on the XZ plane around the origin, like `shapes::Torus`
+#[derive(Debug, Copy, Clone)]
+pub struct Torus {
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl Torus {
+    pub const fn new(major_radius: f64, minor_radius: f64) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Sdf for Torus {
+    fn distance(&self, p: Vec3) -> f64 {
+        let ring = (p.x() * p.x() + p.z() * p.z()).sqrt() - self.major_radius;
+        (ring * ring + p.y() * p.y()).sqrt() - self.minor_radius
+    }
+}
+
+/// Every point within `radius` of the segment from `a` to `b`
+#[derive(Debug, Copy, Clone)]
+pub struct Capsule {
+    a: Vec3,
+    b: Vec3,
+    radius: f64,
+}
+
+impl Capsule {
+    pub const fn new(a: Vec3, b: Vec3, radius: f64) -> Self {
+        Self { a, b, radius }
+    }
+}
+
+impl Sdf for Capsule {
+    fn distance(&self, p: Vec3) -> f64 {
+        let pa = p - self.a;
+        let ba = self.b - self.a;
+        let h = (pa.dot(&ba) / ba.dot(&ba)).clamp(0.0, 1.0);
+
+        (pa - ba * h).length() - self.radius
+    }
+}
+
+/// Points further than this from the origin have escaped the fractal
+const BAILOUT: f64 = 2.0;
+
+/// 3D take on the Mandelbrot set, fits inside a sphere of radius 1.2 or so
+/// around the origin. More `iterations` show finer detail but take longer to trace.
+/// The distance is only an estimate, see
+/// http://blog.hvidtfeldts.net/index.php/2011/09/distance-estimated-3d-fractals-v-the-mandelbulb-different-de-approximations/
+#[derive(Debug, Copy, Clone)]
+pub struct Mandelbulb {
+    power: f64,
+    iterations: u32,
+}
+
+impl Mandelbulb {
+    pub const fn new(power: f64, iterations: u32) -> Self {
+        Self { power, iterations }
+    }
+}
+
+impl Sdf for Mandelbulb {
+    fn distance(&self, p: Vec3) -> f64 {
+        let mut z = p;
+        let mut dr = 1.0;
+        let mut r = z.length();
+
+        for _ in 0..self.iterations {
+            if r > BAILOUT || r == 0.0 {
+                break;
+            }
+
+            // Raise z to the power in spherical coordinates and add the starting point
+            let theta = (z.z() / r).acos() * self.power;
+            let phi = f64::atan2(z.y(), z.x()) * self.power;
+            dr = r.powf(self.power - 1.0) * self.power * dr + 1.0;
+
+            z = Vec3::new(
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos(),
+            ) * r.powf(self.power)
+                + p;
+            r = z.length();
+        }
+
+        0.5 * r.ln() * r / dr
+    }
+}
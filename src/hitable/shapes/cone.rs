@@ -0,0 +1,112 @@
+use crate::{
+    hitable::{
+        shapes::{azimuth, hit_disk},
+        HitRecord, Hitable,
+    },
+    types::{Ray, Vec3},
+    Aabb, Material,
+};
+
+/// A cone with its base on the XZ plane at `center` and its tip `height` above it.
+///
+/// The base is open unless `capped`. On the side u goes around the cone and
+/// v from the base to the tip. On the base v goes from the center out.
+#[derive(Clone)]
+pub struct Cone<T: Material> {
+    center: Vec3,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: T,
+}
+
+impl<T: Material> Cone<T> {
+    pub fn new(center: Vec3, radius: f64, height: f64, material: T) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            capped: false,
+            material,
+        }
+    }
+
+    /// Closes the base with a disk
+    pub fn capped(mut self) -> Self {
+        self.capped = true;
+        self
+    }
+}
+
+impl<T: Material> Hitable for Cone<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let o = ray.origin - self.center;
+        let d = ray.direction;
+
+        let mut closest_so_far = t_max;
+        let mut closest = None;
+
+        // Side, x^2 + z^2 = (k * (h - y))^2 where k is how much the radius
+        // shrinks for every unit of height
+        let k = self.radius / self.height;
+        let k2 = k * k;
+        let h = self.height - o.y();
+
+        let a = d.x() * d.x() + d.z() * d.z() - k2 * d.y() * d.y();
+        let b = o.x() * d.x() + o.z() * d.z() + k2 * h * d.y();
+        let c = o.x() * o.x() + o.z() * o.z() - k2 * h * h;
+
+        let roots = if a.abs() < 1e-12 {
+            // Ray parallel to the slope of the cone only crosses it once
+            if b == 0.0 {
+                [f64::NAN, f64::NAN]
+            } else {
+                [-c / (2.0 * b), f64::NAN]
+            }
+        } else {
+            let discriminant = b * b - a * c;
+            if discriminant < 0.0 {
+                [f64::NAN, f64::NAN]
+            } else {
+                let root = discriminant.sqrt();
+                let (t0, t1) = ((-b - root) / a, (-b + root) / a);
+                [t0.min(t1), t0.max(t1)]
+            }
+        };
+
+        for t in roots {
+            let p = o + d * t;
+            // The equation also describes a second cone mirrored above the tip
+            if t > t_min && t < closest_so_far && (0.0..=self.height).contains(&p.y()) {
+                let normal = Vec3::new(p.x(), k2 * (self.height - p.y()), p.z()).unit_vector();
+                let uv = (azimuth(p.x(), p.z()), p.y() / self.height);
+
+                closest_so_far = t;
+                closest = Some((t, normal, uv));
+                break;
+            }
+        }
+
+        if self.capped {
+            if let Some((t, p)) = hit_disk(o, d, 0.0, self.radius, t_min, closest_so_far) {
+                let r = (p.x() * p.x() + p.z() * p.z()).sqrt();
+                let uv = (azimuth(p.x(), p.z()), r / self.radius);
+
+                closest = Some((t, Vec3::new(0.0, -1.0, 0.0), uv));
+            }
+        }
+
+        let (t, normal, uv) = closest?;
+        let mut hit_rec = HitRecord::new(t, ray.point_at_parameter(t), normal, &self.material, uv);
+        hit_rec.set_face_normal(ray);
+
+        Some(hit_rec)
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        Some(Aabb::new(
+            self.center - Vec3::new(self.radius, 0.0, self.radius),
+            self.center + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+}
@@ -1,7 +1,13 @@
 use std::sync::Arc;
 
+use rand::prelude::SmallRng;
+
 use crate::{
-    hitable::{hitable_list::HitableList, shapes::RectBuilder, HitRecord, Hitable},
+    hitable::{
+        hitable_list::HitableList,
+        shapes::{Quad, RectBuilder},
+        HitRecord, Hitable,
+    },
     materials::{Material, MaterialBuilder},
     types::{Ray, Vec3},
     Aabb,
@@ -22,6 +28,37 @@ impl Cuboid {
         }
     }
 
+    /// A box in any orientation, with a corner at `origin` and edges along `a`, `b` & `c`.
+    /// Edges that aren't perpendicular give a parallelepiped
+    pub fn from_edges(
+        origin: Vec3,
+        a: Vec3,
+        b: Vec3,
+        c: Vec3,
+        mat: impl Material + Clone + 'static,
+    ) -> Self {
+        let mut sides = HitableList {
+            list: Vec::with_capacity(6),
+        };
+
+        sides.push(Arc::new(Quad::new(origin, a, b, mat.clone())));
+        sides.push(Arc::new(Quad::new(origin + c, a, b, mat.clone())));
+        sides.push(Arc::new(Quad::new(origin, a, c, mat.clone())));
+        sides.push(Arc::new(Quad::new(origin + b, a, c, mat.clone())));
+        sides.push(Arc::new(Quad::new(origin, b, c, mat.clone())));
+        sides.push(Arc::new(Quad::new(origin + a, b, c, mat)));
+
+        let bbox = sides
+            .bounding_box(0.0, 1.0)
+            .expect("quads always have a bounding box");
+
+        Self {
+            min: bbox.min,
+            max: bbox.max,
+            sides,
+        }
+    }
+
     fn build_cuboid(p0: Vec3, p1: Vec3, mat: impl Material + Clone + 'static) -> HitableList {
         let mut sides = HitableList {
             list: Vec::with_capacity(6),
@@ -84,4 +121,12 @@ impl Hitable for Cuboid {
     fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
         Some(Aabb::new(self.min, self.max))
     }
+
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        self.sides.pdf_value(ray)
+    }
+
+    fn random(&self, origin: Vec3, time: f64, rng: &mut SmallRng) -> Vec3 {
+        self.sides.random(origin, time, rng)
+    }
 }
@@ -0,0 +1,342 @@
+use crate::{
+    hitable::{shapes::flat_bvh::FlatBvh, Accelerator, BuildStats, HitRecord, Hitable},
+    types::{Ray, Vec3},
+    Aabb, Material,
+};
+
+/// Pieces every curve is cut into before building the BVH, so long curves get tighter boxes
+const SEGMENTS_PER_CURVE: usize = 4;
+
+/// Most times a piece is halved while looking for the hit
+const MAX_DEPTH: i32 = 10;
+
+/// A cubic Bézier curve whose width changes linearly from the first point to the last
+#[derive(Debug, Clone, Copy)]
+pub struct Curve {
+    points: [Vec3; 4],
+    widths: (f64, f64),
+}
+
+impl Curve {
+    pub fn new(points: [Vec3; 4], widths: (f64, f64)) -> Self {
+        Self { points, widths }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Tube,
+    Ribbon,
+}
+
+/// A set of thin curves hit as a single object, for hair, fur and grass.
+///
+/// Curves are round tubes by default, or flat ribbons that always face the ray
+/// with `ribbons`, which is plenty for strands not much wider than a pixel.
+/// u goes along each curve and v across it, the tangent of the hit record
+/// is how the point moves along the curve with u.
+///
+/// Curves are intersected by splitting them in the space of the ray until the
+/// pieces are close enough to straight lines.
+/// Koji Nakamaru & Yoshio Ohno, Ray Tracing For Curves Primitive, WSCG 2002
+pub struct Curves<T: Material> {
+    segments: Vec<Segment>,
+    bvh: FlatBvh,
+    shape: Shape,
+    material: T,
+}
+
+struct Segment {
+    points: [Vec3; 4],
+    // Widths at both ends of the piece
+    widths: (f64, f64),
+    // Part of its curve the piece covers
+    u: (f64, f64),
+}
+
+impl<T: Material> Curves<T> {
+    pub fn new(curves: Vec<Curve>, material: T) -> Self {
+        assert!(!curves.is_empty(), "no curves to hit");
+
+        let segments = curves
+            .iter()
+            .flat_map(|curve| {
+                (0..SEGMENTS_PER_CURVE).map(move |i| {
+                    let u0 = i as f64 / SEGMENTS_PER_CURVE as f64;
+                    let u1 = (i + 1) as f64 / SEGMENTS_PER_CURVE as f64;
+                    Segment {
+                        points: sub_curve(&curve.points, u0, u1),
+                        widths: (lerp(u0, curve.widths), lerp(u1, curve.widths)),
+                        u: (u0, u1),
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Control points contain the curve, padding by the width covers the sides
+        let boxes = segments
+            .iter()
+            .map(|segment| {
+                let (min, max) = segment.points.iter().fold(
+                    (Vec3::splat(f64::MAX), Vec3::splat(f64::MIN)),
+                    |(min, max), &p| (min.min(p), max.max(p)),
+                );
+                let padding = Vec3::splat(0.5 * segment.widths.0.max(segment.widths.1));
+                Aabb::new(min - padding, max + padding)
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            bvh: FlatBvh::new(&boxes),
+            segments,
+            shape: Shape::Tube,
+            material,
+        }
+    }
+
+    /// Draw the curves as flat strips turned towards the ray instead of tubes
+    pub fn ribbons(mut self) -> Self {
+        self.shape = Shape::Ribbon;
+        self
+    }
+}
+
+impl<T: Material> Hitable for Curves<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Frame with the ray along Z, where the curve is hit if it passes
+        // within half its width of the origin on the XY plane
+        let length = ray.direction.length();
+        let direction = ray.direction / length;
+        let (x_axis, y_axis) = orthonormal_basis(direction);
+        let to_ray_space = |p: Vec3| {
+            let p = p - ray.origin;
+            Vec3::new(p.dot(&x_axis), p.dot(&y_axis), p.dot(&direction))
+        };
+
+        let mut closest = None;
+        self.bvh
+            .traverse(ray, t_min, t_max, |index, closest_so_far| {
+                let segment = &self.segments[index as usize];
+                let points = segment.points.map(to_ray_space);
+
+                // Enough splits for the pieces to be within a fraction of the width
+                // of a straight line, from the second differences of the control points
+                let flatness = (0..2)
+                    .map(|i| {
+                        let d = points[i] - points[i + 1] * 2.0 + points[i + 2];
+                        d.x().abs().max(d.y().abs())
+                    })
+                    .fold(0.0, f64::max);
+                let epsilon = 0.05 * segment.widths.0.max(segment.widths.1);
+                let depth = if flatness > 0.0 && epsilon > 0.0 {
+                    ((std::f64::consts::SQRT_2 * 6.0 * flatness / (8.0 * epsilon)).log2() / 2.0)
+                        .round()
+                        .clamp(0.0, MAX_DEPTH as f64) as i32
+                } else {
+                    0
+                };
+
+                let (z, u) = hit_segment(
+                    &points,
+                    (0.0, 1.0),
+                    segment.widths,
+                    depth,
+                    t_min * length,
+                    closest_so_far * length,
+                )?;
+
+                closest = Some((index, z / length, u));
+                Some(z / length)
+            });
+
+        let (index, t, u) = closest?;
+        let segment = &self.segments[index as usize];
+
+        let (center, derivative) = evaluate(&segment.points, u);
+        let tangent = derivative.unit_vector();
+        let radius = 0.5 * lerp(u, segment.widths);
+
+        // Direction across the curve that faces back along the ray
+        let facing = tangent * direction.dot(&tangent) - direction;
+        let facing = if facing.sq_len() > 0.0 {
+            facing.unit_vector()
+        } else {
+            -direction
+        };
+        let side = tangent.cross(&facing);
+
+        let p = ray.point_at_parameter(t);
+        // Tapered ends can get down to no width at all
+        let offset = ((p - center).dot(&side) / radius.max(f64::EPSILON)).clamp(-1.0, 1.0);
+        let uv = (lerp(u, segment.u), 0.5 * (offset + 1.0));
+
+        let (t, normal) = match self.shape {
+            Shape::Ribbon => (t, facing),
+            Shape::Tube => {
+                // The hit is on the plane through the middle of the tube,
+                // move it forward to the round surface
+                let height = (1.0 - offset * offset).sqrt();
+                let tube_t = t - radius * height / facing.dot(&-ray.direction);
+                (
+                    if tube_t > t_min { tube_t } else { t },
+                    side * offset + facing * height,
+                )
+            }
+        };
+
+        let mut hit_rec = HitRecord::new(t, ray.point_at_parameter(t), normal, &self.material, uv);
+        // Each segment only covers part of the curve's u
+        hit_rec.tangent = Some(derivative / (segment.u.1 - segment.u.0));
+        hit_rec.bitangent = Some(side * (2.0 * radius));
+        hit_rec.set_face_normal(ray);
+
+        Some(hit_rec)
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        Some(self.bvh.bbox())
+    }
+}
+
+impl<T: Material> Accelerator for Curves<T> {
+    fn stats(&self) -> BuildStats {
+        self.bvh.stats()
+    }
+}
+
+/// Closest hit with a piece of curve already in ray space, returns the distance
+/// along the ray and u along the whole segment
+fn hit_segment(
+    points: &[Vec3; 4],
+    (u0, u1): (f64, f64),
+    widths: (f64, f64),
+    depth: i32,
+    z_min: f64,
+    z_max: f64,
+) -> Option<(f64, f64)> {
+    let half_width = 0.5 * lerp(u0, widths).max(lerp(u1, widths));
+    let (min, max) = points.iter().fold(
+        (Vec3::splat(f64::MAX), Vec3::splat(f64::MIN)),
+        |(min, max), &p| (min.min(p), max.max(p)),
+    );
+    if min.x() - half_width > 0.0
+        || max.x() + half_width < 0.0
+        || min.y() - half_width > 0.0
+        || max.y() + half_width < 0.0
+        || min.z() - half_width > z_max
+        || max.z() + half_width < z_min
+    {
+        return None;
+    }
+
+    if depth > 0 {
+        let mid = 0.5 * (u0 + u1);
+        let left = hit_segment(
+            &sub_curve(points, 0.0, 0.5),
+            (u0, mid),
+            widths,
+            depth - 1,
+            z_min,
+            z_max,
+        );
+        let right = hit_segment(
+            &sub_curve(points, 0.5, 1.0),
+            (mid, u1),
+            widths,
+            depth - 1,
+            z_min,
+            left.map_or(z_max, |(z, _)| z),
+        );
+        return right.or(left);
+    }
+
+    // Skip rays that pass beyond either end of the piece, measured along the
+    // tangents at the ends so neighbouring pieces meet without gaps
+    let start = points[0];
+    let end = points[3];
+    if (points[1].x() - start.x()) * -start.x() + (points[1].y() - start.y()) * -start.y() < 0.0
+        || (points[2].x() - end.x()) * -end.x() + (points[2].y() - end.y()) * -end.y() < 0.0
+    {
+        return None;
+    }
+
+    // Closest point to the ray treating the piece as a line
+    let (dx, dy) = (end.x() - start.x(), end.y() - start.y());
+    let denominator = dx * dx + dy * dy;
+    if denominator == 0.0 {
+        return None;
+    }
+    let w = ((-start.x() * dx - start.y() * dy) / denominator).clamp(0.0, 1.0);
+    let u = lerp(w, (u0, u1));
+
+    let (p, _) = evaluate(points, w);
+    let half_width = 0.5 * lerp(u, widths);
+    if p.x() * p.x() + p.y() * p.y() > half_width * half_width {
+        return None;
+    }
+
+    if p.z() <= z_min || p.z() >= z_max {
+        return None;
+    }
+
+    Some((p.z(), u))
+}
+
+/// Point on the curve at `u` and the derivative there
+fn evaluate(points: &[Vec3; 4], u: f64) -> (Vec3, Vec3) {
+    // de Casteljau's algorithm
+    let a = [
+        mix(points[0], points[1], u),
+        mix(points[1], points[2], u),
+        mix(points[2], points[3], u),
+    ];
+    let b = [mix(a[0], a[1], u), mix(a[1], a[2], u)];
+
+    // Coincident control points leave a zero derivative at the ends
+    let derivative = if (b[1] - b[0]).sq_len() > 0.0 {
+        (b[1] - b[0]) * 3.0
+    } else {
+        points[3] - points[0]
+    };
+
+    (mix(b[0], b[1], u), derivative)
+}
+
+/// Control points of the part of the curve between `u0` and `u1`
+fn sub_curve(points: &[Vec3; 4], u0: f64, u1: f64) -> [Vec3; 4] {
+    [
+        blossom(points, u0, u0, u0),
+        blossom(points, u0, u0, u1),
+        blossom(points, u0, u1, u1),
+        blossom(points, u1, u1, u1),
+    ]
+}
+
+fn blossom(points: &[Vec3; 4], u0: f64, u1: f64, u2: f64) -> Vec3 {
+    let a = [
+        mix(points[0], points[1], u0),
+        mix(points[1], points[2], u0),
+        mix(points[2], points[3], u0),
+    ];
+    let b = [mix(a[0], a[1], u1), mix(a[1], a[2], u1)];
+    mix(b[0], b[1], u2)
+}
+
+fn lerp(t: f64, (a, b): (f64, f64)) -> f64 {
+    a + (b - a) * t
+}
+
+/// Two unit vectors perpendicular to `v` and each other
+fn orthonormal_basis(v: Vec3) -> (Vec3, Vec3) {
+    let other = if v.x().abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let x = v.cross(&other).unit_vector();
+    (x, v.cross(&x))
+}
+
+fn mix(a: Vec3, b: Vec3, t: f64) -> Vec3 {
+    a + (b - a) * t
+}
@@ -0,0 +1,102 @@
+use crate::{
+    hitable::{
+        shapes::{azimuth, disk_tangents, hit_disk},
+        HitRecord, Hitable,
+    },
+    types::{Ray, Vec3},
+    Aabb, Material,
+};
+
+/// A cylinder standing on the XZ plane at `center` and rising along +Y.
+///
+/// Open at both ends unless `capped`. On the side u goes around the cylinder
+/// and v from the bottom to the top. On the caps v goes from the center out.
+#[derive(Clone)]
+pub struct Cylinder<T: Material> {
+    center: Vec3,
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: T,
+}
+
+impl<T: Material> Cylinder<T> {
+    pub fn new(center: Vec3, radius: f64, height: f64, material: T) -> Self {
+        Self {
+            center,
+            radius,
+            height,
+            capped: false,
+            material,
+        }
+    }
+
+    /// Closes both ends with disks
+    pub fn capped(mut self) -> Self {
+        self.capped = true;
+        self
+    }
+}
+
+impl<T: Material> Hitable for Cylinder<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let o = ray.origin - self.center;
+        let d = ray.direction;
+
+        let mut closest_so_far = t_max;
+        let mut closest = None;
+
+        // Side, x^2 + z^2 = r^2 between the caps
+        let a = d.x() * d.x() + d.z() * d.z();
+        let b = o.x() * d.x() + o.z() * d.z();
+        let c = o.x() * o.x() + o.z() * o.z() - self.radius * self.radius;
+        let discriminant = b * b - a * c;
+
+        if a > 0.0 && discriminant >= 0.0 {
+            let root = discriminant.sqrt();
+            for t in [(-b - root) / a, (-b + root) / a] {
+                let p = o + d * t;
+                if t > t_min && t < closest_so_far && (0.0..=self.height).contains(&p.y()) {
+                    let normal = Vec3::new(p.x(), 0.0, p.z()) / self.radius;
+                    let uv = (azimuth(p.x(), p.z()), p.y() / self.height);
+                    let around = Vec3::new(p.z(), 0.0, -p.x()) * (2.0 * std::f64::consts::PI);
+                    let tangents = Some((around, Vec3::new(0.0, self.height, 0.0)));
+
+                    closest_so_far = t;
+                    closest = Some((t, normal, uv, tangents));
+                    break;
+                }
+            }
+        }
+
+        if self.capped {
+            for (y, normal) in [(0.0, -1.0), (self.height, 1.0)] {
+                if let Some((t, p)) = hit_disk(o, d, y, self.radius, t_min, closest_so_far) {
+                    let r = (p.x() * p.x() + p.z() * p.z()).sqrt();
+                    let uv = (azimuth(p.x(), p.z()), r / self.radius);
+                    let tangents = disk_tangents(p, self.radius);
+
+                    closest_so_far = t;
+                    closest = Some((t, Vec3::new(0.0, normal, 0.0), uv, tangents));
+                }
+            }
+        }
+
+        let (t, normal, uv, tangents) = closest?;
+        let mut hit_rec = HitRecord::new(t, ray.point_at_parameter(t), normal, &self.material, uv);
+        if let Some((tangent, bitangent)) = tangents {
+            hit_rec.tangent = Some(tangent);
+            hit_rec.bitangent = Some(bitangent);
+        }
+        hit_rec.set_face_normal(ray);
+
+        Some(hit_rec)
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        Some(Aabb::new(
+            self.center - Vec3::new(self.radius, 0.0, self.radius),
+            self.center + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{
+    hitable::{
+        shapes::{azimuth, disk_tangents, hit_disk},
+        HitRecord, Hitable,
+    },
+    types::{Ray, Vec3},
+    Aabb, Material,
+};
+
+/// A flat circle facing +Y. Tilt it with the `Hitable::rotate_*` wrappers.
+/// u goes around the disk and v from the center out to the edge
+#[derive(Clone)]
+pub struct Disk<T: Material> {
+    center: Vec3,
+    radius: f64,
+    material: T,
+}
+
+impl<T: Material> Disk<T> {
+    pub fn new(center: Vec3, radius: f64, material: T) -> Self {
+        Self {
+            center,
+            radius,
+            material,
+        }
+    }
+}
+
+impl<T: Material> Hitable for Disk<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let origin = ray.origin - self.center;
+        let (t, p) = hit_disk(origin, ray.direction, 0.0, self.radius, t_min, t_max)?;
+
+        let r = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        let mut hit_rec = HitRecord::new(
+            t,
+            ray.point_at_parameter(t),
+            Vec3::new(0.0, 1.0, 0.0),
+            &self.material,
+            (azimuth(p.x(), p.z()), r / self.radius),
+        );
+        if let Some((tangent, bitangent)) = disk_tangents(p, self.radius) {
+            hit_rec.tangent = Some(tangent);
+            hit_rec.bitangent = Some(bitangent);
+        }
+        hit_rec.set_face_normal(ray);
+
+        Some(hit_rec)
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        let extent = Vec3::new(self.radius, 0.0001, self.radius);
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+}
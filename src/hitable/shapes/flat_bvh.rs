@@ -0,0 +1,200 @@
+use std::cmp::Ordering;
+
+use crate::{
+    hitable::{stats, BuildStats},
+    types::{Ray, Vec3},
+    Aabb,
+};
+
+/// Primitives per leaf
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Traversal stack size. Median splits keep the tree balanced so this covers
+/// more primitives than would fit in memory.
+const STACK_SIZE: usize = 64;
+
+/// BVH over the primitives of a single shape, stored as a flat array of nodes.
+///
+/// Shapes made of many small pieces, like the triangles of a mesh, keep one of
+/// these inside and are hit as a single object. The tree only knows primitive
+/// indices and their boxes, testing a primitive is up to the shape.
+pub(super) struct FlatBvh {
+    nodes: Vec<Node>,
+    // Primitive indices, ordered so each leaf covers a contiguous range
+    primitives: Vec<u32>,
+}
+
+struct Node {
+    bbox: Aabb,
+    // For leaves the first primitive in `primitives`, for inner nodes the index of
+    // the right child. The left child always comes right after its parent.
+    offset: u32,
+    // Number of primitives in a leaf, 0 for inner nodes
+    count: u32,
+    // Axis inner nodes are split along, used to visit the nearer child first
+    axis: u8,
+}
+
+impl FlatBvh {
+    /// Builds the tree over one bounding box per primitive
+    pub fn new(boxes: &[Aabb]) -> Self {
+        assert!(!boxes.is_empty(), "bvh has no primitives");
+
+        let centroids = boxes
+            .iter()
+            .map(|bbox| (bbox.min + bbox.max) * 0.5)
+            .collect::<Vec<_>>();
+
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * boxes.len() / MAX_LEAF_SIZE),
+            primitives: Vec::new(),
+        };
+
+        let mut primitives = (0..boxes.len() as u32).collect::<Vec<_>>();
+        bvh.build(&mut primitives, 0, boxes, &centroids);
+        bvh.primitives = primitives;
+
+        bvh
+    }
+
+    fn build(&mut self, primitives: &mut [u32], start: usize, boxes: &[Aabb], centroids: &[Vec3]) {
+        let bbox = primitives
+            .iter()
+            .map(|&i| boxes[i as usize])
+            .reduce(Aabb::surrounding_box)
+            .unwrap();
+
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            bbox,
+            offset: start as u32,
+            count: primitives.len() as u32,
+            axis: 0,
+        });
+
+        if primitives.len() <= MAX_LEAF_SIZE {
+            return;
+        }
+
+        // Split at the median centroid along the longest axis of the centroid bounds
+        let (min, max) = primitives.iter().fold(
+            (Vec3::splat(f64::MAX), Vec3::splat(f64::MIN)),
+            |(min, max), &i| {
+                (
+                    min.min(centroids[i as usize]),
+                    max.max(centroids[i as usize]),
+                )
+            },
+        );
+        let extent = max - min;
+        if extent.x().max(extent.y()).max(extent.z()) <= 0.0 {
+            // Every centroid is in the same spot, no split can separate them
+            return;
+        }
+
+        let axis = if extent.x() > extent.y() && extent.x() > extent.z() {
+            0
+        } else if extent.y() > extent.z() {
+            1
+        } else {
+            2
+        };
+        let key = |i: &u32| {
+            let c = centroids[*i as usize];
+            match axis {
+                0 => c.x(),
+                1 => c.y(),
+                _ => c.z(),
+            }
+        };
+
+        let mid = primitives.len() / 2;
+        primitives.select_nth_unstable_by(mid, |a, b| {
+            key(a).partial_cmp(&key(b)).unwrap_or(Ordering::Equal)
+        });
+
+        let (left, right) = primitives.split_at_mut(mid);
+        self.build(left, start, boxes, centroids);
+
+        let right_index = self.nodes.len() as u32;
+        self.build(right, start + mid, boxes, centroids);
+
+        self.nodes[index].offset = right_index;
+        self.nodes[index].count = 0;
+        self.nodes[index].axis = axis;
+    }
+
+    pub fn bbox(&self) -> Aabb {
+        self.nodes[0].bbox
+    }
+
+    /// Calls `test` with every primitive whose leaf the ray goes through and the
+    /// closest t found so far. `test` returns the t of its hit, which then
+    /// limits the rest of the traversal
+    pub fn traverse(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        mut test: impl FnMut(u32, f64) -> Option<f64>,
+    ) {
+        let mut closest_so_far = t_max;
+
+        let mut stack = [0u32; STACK_SIZE];
+        let mut len = 1;
+
+        while len > 0 {
+            len -= 1;
+            let index = stack[len];
+            let node = &self.nodes[index as usize];
+
+            stats::count_box_tests(1);
+            if !node.bbox.hit(ray, t_min, closest_so_far) {
+                continue;
+            }
+
+            if node.count == 0 {
+                // Left child sits right after its parent and holds the lower half,
+                // push the farther child first so the nearer one is popped first
+                let direction = match node.axis {
+                    0 => ray.direction.x(),
+                    1 => ray.direction.y(),
+                    _ => ray.direction.z(),
+                };
+                let (near, far) = if direction < 0.0 {
+                    (node.offset, index + 1)
+                } else {
+                    (index + 1, node.offset)
+                };
+                stack[len] = far;
+                stack[len + 1] = near;
+                len += 2;
+                continue;
+            }
+
+            let start = node.offset as usize;
+            for &primitive in &self.primitives[start..start + node.count as usize] {
+                stats::count_primitive_test();
+                if let Some(t) = test(primitive, closest_so_far) {
+                    closest_so_far = t;
+                }
+            }
+        }
+    }
+
+    pub fn stats(&self) -> BuildStats {
+        let mut stats = BuildStats::default();
+        self.collect_stats(&mut stats, 0, 1, self.bbox().area().max(f64::EPSILON));
+        stats
+    }
+
+    fn collect_stats(&self, stats: &mut BuildStats, index: usize, depth: usize, root_area: f64) {
+        let node = &self.nodes[index];
+        stats.add_node(depth, node.count as usize, node.bbox.area() / root_area);
+
+        if node.count == 0 {
+            self.collect_stats(stats, index + 1, depth + 1, root_area);
+            self.collect_stats(stats, node.offset as usize, depth + 1, root_area);
+        }
+    }
+}
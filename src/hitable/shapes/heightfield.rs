@@ -0,0 +1,286 @@
+use image::{error::ImageError, io::Reader as ImageReader};
+
+use crate::{
+    hitable::{
+        shapes::triangle::{interpolate, intersect, tangents},
+        HitRecord, Hitable,
+    },
+    texture::Perlin,
+    types::{Ray, Vec3},
+    Aabb, Material,
+};
+
+/// Terrain made of a grid of heights, split into two triangles per cell.
+///
+/// Only the heights are stored, triangles and normals are built on the fly for
+/// the cells a ray walks through, so large terrains don't cost much memory.
+/// The grid covers `size` along X and Z from `min`, heights between 0 and 1 are
+/// scaled by `size.y()`. u and v go along X and Z, with v = 1 at the first row
+/// so an image texture lines up with the image the heights came from
+pub struct Heightfield<T: Material> {
+    heights: Vec<f32>,
+    // Number of samples along X & Z
+    width: usize,
+    depth: usize,
+    min: Vec3,
+    size: Vec3,
+    // Size of a single cell along X & Z
+    cell: (f64, f64),
+    material: T,
+}
+
+impl<T: Material> Heightfield<T> {
+    /// `heights` holds `width` samples along X for each of the `depth` rows along Z
+    pub fn new(
+        heights: Vec<f32>,
+        (width, depth): (usize, usize),
+        min: Vec3,
+        size: Vec3,
+        material: T,
+    ) -> Self {
+        assert!(
+            width >= 2 && depth >= 2,
+            "heightfield needs at least 2x2 samples"
+        );
+        assert_eq!(heights.len(), width * depth, "one height per sample");
+
+        Self {
+            heights,
+            width,
+            depth,
+            min,
+            size,
+            cell: (size.x() / (width - 1) as f64, size.z() / (depth - 1) as f64),
+            material,
+        }
+    }
+
+    /// One sample per pixel, black is the lowest point and white the highest
+    pub fn from_image(
+        filename: &str,
+        min: Vec3,
+        size: Vec3,
+        material: T,
+    ) -> Result<Self, ImageError> {
+        let img = ImageReader::open(filename)?.decode()?.to_luma16();
+        let (width, depth) = img.dimensions();
+
+        let heights = img
+            .pixels()
+            .map(|p| p.0[0] as f32 / u16::MAX as f32)
+            .collect();
+
+        Ok(Self::new(
+            heights,
+            (width as usize, depth as usize),
+            min,
+            size,
+            material,
+        ))
+    }
+
+    /// Hills sampled from the turbulence of `perlin`, `frequency` sets how many
+    /// noise cells fit across the terrain. Heights are rescaled to fill `size.y()`
+    pub fn from_perlin(
+        perlin: &Perlin,
+        (width, depth): (usize, usize),
+        frequency: f64,
+        min: Vec3,
+        size: Vec3,
+        material: T,
+    ) -> Self {
+        let mut heights = Vec::with_capacity(width * depth);
+        for j in 0..depth {
+            for i in 0..width {
+                let p = Vec3::new(
+                    i as f64 / (width - 1) as f64,
+                    0.0,
+                    j as f64 / (depth - 1) as f64,
+                ) * frequency;
+                heights.push(perlin.turbulence(p, 7) as f32);
+            }
+        }
+
+        let lowest = heights.iter().copied().fold(f32::MAX, f32::min);
+        let highest = heights.iter().copied().fold(f32::MIN, f32::max);
+        let range = (highest - lowest).max(f32::EPSILON);
+        for h in heights.iter_mut() {
+            *h = (*h - lowest) / range;
+        }
+
+        Self::new(heights, (width, depth), min, size, material)
+    }
+
+    fn height(&self, i: usize, j: usize) -> f64 {
+        self.heights[j * self.width + i] as f64 * self.size.y()
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Vec3 {
+        self.min
+            + Vec3::new(
+                i as f64 * self.cell.0,
+                self.height(i, j),
+                j as f64 * self.cell.1,
+            )
+    }
+
+    /// Normal from the slope to the neighbouring samples, one sided on the edges
+    fn vertex_normal(&self, i: usize, j: usize) -> Vec3 {
+        let (left, right) = (i.saturating_sub(1), (i + 1).min(self.width - 1));
+        let (back, front) = (j.saturating_sub(1), (j + 1).min(self.depth - 1));
+
+        let dx =
+            (self.height(right, j) - self.height(left, j)) / ((right - left) as f64 * self.cell.0);
+        let dz =
+            (self.height(i, front) - self.height(i, back)) / ((front - back) as f64 * self.cell.1);
+
+        Vec3::new(-dx, 1.0, -dz).unit_vector()
+    }
+
+    /// Tests the two triangles of cell (i, j), if the ray gets within their height range
+    /// while it's above the cell between `t_enter` and `t_exit`
+    fn hit_cell(
+        &self,
+        ray: &Ray,
+        (i, j): (usize, usize),
+        t_enter: f64,
+        t_exit: f64,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord> {
+        let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+        let heights = corners.map(|(i, j)| self.height(i, j));
+        let lowest = self.min.y() + heights.iter().copied().fold(f64::MAX, f64::min);
+        let highest = self.min.y() + heights.iter().copied().fold(f64::MIN, f64::max);
+
+        let y_enter = ray.origin.y() + t_enter * ray.direction.y();
+        let y_exit = ray.origin.y() + t_exit * ray.direction.y();
+        if y_enter.max(y_exit) < lowest || y_enter.min(y_exit) > highest {
+            return None;
+        }
+
+        let vertices = corners.map(|(i, j)| self.vertex(i, j));
+
+        // Split along the diagonal from (i, j) to (i + 1, j + 1)
+        let (triangle, (t, barycentric)) = [[0, 2, 1], [0, 3, 2]]
+            .into_iter()
+            .filter_map(|triangle| {
+                let points = triangle.map(|k| vertices[k]);
+                intersect(&points, ray, t_min, t_max).map(|hit| (triangle, hit))
+            })
+            .min_by(|a, b| a.1 .0.total_cmp(&b.1 .0))?;
+
+        let normals = triangle.map(|k| self.vertex_normal(corners[k].0, corners[k].1));
+        let normal = interpolate(&normals, barycentric).unit_vector();
+
+        let uv = |p: Vec3| {
+            (
+                (p.x() - self.min.x()) / self.size.x(),
+                1.0 - (p.z() - self.min.z()) / self.size.z(),
+            )
+        };
+
+        let p = ray.point_at_parameter(t);
+        let mut hit_rec = HitRecord::new(t, p, normal, &self.material, uv(p));
+        let points = triangle.map(|k| vertices[k]);
+        if let Some((tangent, bitangent)) = tangents(&points, &points.map(uv)) {
+            hit_rec.tangent = Some(tangent);
+            hit_rec.bitangent = Some(bitangent);
+        }
+        hit_rec.set_face_normal(ray);
+
+        Some(hit_rec)
+    }
+}
+
+impl<T: Material> Hitable for Heightfield<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let bbox = self.bounding_box(0.0, 1.0)?;
+
+        // Only walk the part of the ray inside the bounding box
+        let t0 = (bbox.min - ray.origin) / ray.direction;
+        let t1 = (bbox.max - ray.origin) / ray.direction;
+        let mut t = t0.min(t1).max_element(t_min);
+        let t_end = t0.max(t1).min_element(t_max);
+
+        if t_end <= t {
+            return None;
+        }
+
+        // 2D DDA over the cells below the ray
+        // Amanatides & Woo, A Fast Voxel Traversal Algorithm for Ray Tracing
+        let cells = (self.width as isize - 1, self.depth as isize - 1);
+        let start = ray.point_at_parameter(t);
+        let mut i = (((start.x() - self.min.x()) / self.cell.0) as isize).clamp(0, cells.0 - 1);
+        let mut j = (((start.z() - self.min.z()) / self.cell.1) as isize).clamp(0, cells.1 - 1);
+
+        let axis = |origin: f64, direction: f64, min: f64, cell: f64, index: isize| {
+            if direction > 0.0 {
+                (
+                    1,
+                    (min + (index + 1) as f64 * cell - origin) / direction,
+                    cell / direction,
+                )
+            } else if direction < 0.0 {
+                (
+                    -1,
+                    (min + index as f64 * cell - origin) / direction,
+                    -cell / direction,
+                )
+            } else {
+                (0, f64::INFINITY, f64::INFINITY)
+            }
+        };
+        let (step_i, mut t_next_i, t_delta_i) = axis(
+            ray.origin.x(),
+            ray.direction.x(),
+            self.min.x(),
+            self.cell.0,
+            i,
+        );
+        let (step_j, mut t_next_j, t_delta_j) = axis(
+            ray.origin.z(),
+            ray.direction.z(),
+            self.min.z(),
+            self.cell.1,
+            j,
+        );
+
+        loop {
+            let t_exit = t_next_i.min(t_next_j).min(t_end);
+
+            if let Some(hit) = self.hit_cell(ray, (i as usize, j as usize), t, t_exit, t_min, t_max)
+            {
+                return Some(hit);
+            }
+
+            if t_exit >= t_end {
+                return None;
+            }
+
+            if t_next_i < t_next_j {
+                i += step_i;
+                t = t_next_i;
+                t_next_i += t_delta_i;
+            } else {
+                j += step_j;
+                t = t_next_j;
+                t_next_j += t_delta_j;
+            }
+
+            if i < 0 || i >= cells.0 || j < 0 || j >= cells.1 {
+                return None;
+            }
+        }
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        // Keep some thickness when the terrain is flat
+        let height = self.size.y().max(0.0001);
+
+        Some(Aabb::new(
+            self.min,
+            self.min + Vec3::new(self.size.x(), height, self.size.z()),
+        ))
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{
+    hitable::{HitRecord, Hitable},
+    types::{Ray, Vec3},
+    Aabb, Material,
+};
+
+/// A plane through `point` that goes on forever, for floors and horizons.
+///
+/// It has no bounding box so it can't go in a `BvhNode`, add it to a `Scene`
+/// instead which keeps unbounded objects out of the tree. Texture coordinates
+/// are the position on the plane in units of `tile_size`, they grow without
+/// bound and it's up to the texture's `WrapMode` to repeat across them.
+#[derive(Clone)]
+pub struct InfinitePlane<T: Material> {
+    point: Vec3,
+    normal: Vec3,
+    // Directions on the plane u and v increase in, u x v is the normal
+    u_axis: Vec3,
+    v_axis: Vec3,
+    tile_size: f64,
+    material: T,
+}
+
+impl<T: Material> InfinitePlane<T> {
+    pub fn new(point: Vec3, normal: Vec3, material: T) -> Self {
+        let normal = normal.unit_vector();
+        let other = if normal.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let u_axis = (other - normal * normal.dot(&other)).unit_vector();
+
+        Self {
+            point,
+            normal,
+            u_axis,
+            v_axis: normal.cross(&u_axis),
+            tile_size: 1.0,
+            material,
+        }
+    }
+
+    /// Distance on the plane over which the texture coordinates go from 0 to 1
+    pub fn with_tile_size(mut self, tile_size: f64) -> Self {
+        self.tile_size = tile_size;
+        self
+    }
+}
+
+impl<T: Material> Hitable for InfinitePlane<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let denominator = self.normal.dot(&ray.direction);
+
+        // Ray is parallel to the plane
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = self.normal.dot(&(self.point - ray.origin)) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let p = ray.point_at_parameter(t);
+        let offset = p - self.point;
+        let u = offset.dot(&self.u_axis) / self.tile_size;
+        let v = offset.dot(&self.v_axis) / self.tile_size;
+
+        let mut hit_rec = HitRecord::new(t, p, self.normal, &self.material, (u, v));
+        hit_rec.tangent = Some(self.u_axis * self.tile_size);
+        hit_rec.bitangent = Some(self.v_axis * self.tile_size);
+        hit_rec.set_face_normal(ray);
+
+        Some(hit_rec)
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        None
+    }
+}
@@ -1,9 +1,83 @@
+mod cone;
 mod cuboid;
+mod curves;
+mod cylinder;
+mod disk;
+mod flat_bvh;
+mod heightfield;
+mod infinite_plane;
 mod moving_sphere;
+mod quad;
 mod rectangle;
 mod sphere;
+mod torus;
+mod triangle;
+mod triangle_mesh;
 
+pub use cone::Cone;
 pub use cuboid::Cuboid;
+pub use curves::{Curve, Curves};
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use heightfield::Heightfield;
+pub use infinite_plane::InfinitePlane;
 pub use moving_sphere::MovingSphere;
+pub use quad::Quad;
 pub use rectangle::RectBuilder;
 pub use sphere::Sphere;
+pub use torus::Torus;
+pub use triangle::Triangle;
+pub use triangle_mesh::{MeshData, TriangleMesh};
+
+use crate::types::Vec3;
+
+/// Angle around the Y axis starting from -X, scaled to [0, 1].
+/// Same convention `sphere_uv` uses for u
+fn azimuth(x: f64, z: f64) -> f64 {
+    (f64::atan2(-z, x) + std::f64::consts::PI) / (2.0 * std::f64::consts::PI)
+}
+
+/// p is a point on the sphere of radius 1 & center at origin
+/// u is between [0,1]. Angle around Y axis from -X axis
+/// v is between [0,1]. Angle from -Y to +Y axis
+pub fn sphere_uv(p: Vec3) -> (f64, f64) {
+    let u = azimuth(p.x(), p.z());
+    let v = (-p.y()).acos() / std::f64::consts::PI;
+
+    (u, v)
+}
+
+/// How p changes with u & v on a disk around the Y axis, with u from `azimuth`
+/// and v from the center out to `radius`. Neither has a direction at the center
+fn disk_tangents(p: Vec3, radius: f64) -> Option<(Vec3, Vec3)> {
+    let around = Vec3::new(p.z(), 0.0, -p.x());
+    if around.sq_len() < 1e-12 {
+        return None;
+    }
+
+    let outwards = Vec3::new(p.x(), 0.0, p.z()).unit_vector();
+    Some((around * (2.0 * std::f64::consts::PI), outwards * radius))
+}
+
+/// Hits a disk facing along Y at height `y` and centered on the Y axis.
+/// Returns t and the hit point
+fn hit_disk(
+    origin: Vec3,
+    direction: Vec3,
+    y: f64,
+    radius: f64,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, Vec3)> {
+    let t = (y - origin.y()) / direction.y();
+    if !(t > t_min && t < t_max) {
+        return None;
+    }
+
+    let p = origin + direction * t;
+    if p.x() * p.x() + p.z() * p.z() > radius * radius {
+        return None;
+    }
+
+    Some((t, p))
+}
@@ -1,5 +1,5 @@
 use crate::{
-    hitable::{HitRecord, Hitable},
+    hitable::{shapes::sphere_uv, HitRecord, Hitable},
     types::{Ray, Vec3},
     Aabb, Material,
 };
@@ -37,19 +37,6 @@ impl<T: Material + Sized> MovingSphere<T> {
             + (self.center_end - self.center_start)
                 * ((time - self.time_start) / (self.time_end - self.time_start))
     }
-
-    /// p is a point on the sphere of radius 1 & center at origin
-    /// u is between [0,1]. Angle around Y axis from -X axis
-    /// v is between [0,1]. Angle from -Y to +Y axis
-    pub fn get_uv(p: Vec3) -> (f64, f64) {
-        let theta = (-p.y()).acos();
-        let phi = f64::atan2(-p.z(), p.x()) + std::f64::consts::PI;
-
-        let u = phi / (2.0 * std::f64::consts::PI);
-        let v = theta / std::f64::consts::PI;
-
-        (u, v)
-    }
 }
 
 impl<T: Material + Sized> Hitable for MovingSphere<T> {
@@ -72,7 +59,7 @@ impl<T: Material + Sized> Hitable for MovingSphere<T> {
                 let normal = (p - self.center(ray.time())) / self.radius;
 
                 let mut hit_rec =
-                    HitRecord::new(root, p, normal, &self.material, Self::get_uv(normal));
+                    HitRecord::new(root, p, normal, &self.material, sphere_uv(normal));
 
                 hit_rec.set_face_normal(ray);
 
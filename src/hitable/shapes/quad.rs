@@ -0,0 +1,118 @@
+use rand::{prelude::SmallRng, Rng};
+
+use crate::{
+    hitable::{HitRecord, Hitable},
+    types::{Ray, Vec3},
+    Aabb, Material,
+};
+
+/// Same padding `Rectangle` uses to give flat boxes some volume
+const BOX_PADDING: f64 = 0.0001;
+
+/// A parallelogram with a corner at `origin` and sides along `u` and `v`.
+///
+/// Unlike `Rectangle` it can face any direction. Texture coordinates go from
+/// (0, 0) at `origin` to (1, 1) at `origin + u + v`, and the normal points
+/// along `u x v`.
+#[derive(Clone)]
+pub struct Quad<T: Material> {
+    origin: Vec3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    // Plane of the quad is normal . p = d
+    d: f64,
+    // Turns a point on the plane into its coordinates along u and v
+    w: Vec3,
+    area: f64,
+    material: T,
+}
+
+impl<T: Material> Quad<T> {
+    pub fn new(origin: Vec3, u: Vec3, v: Vec3, material: T) -> Self {
+        let n = u.cross(&v);
+        let normal = n.unit_vector();
+
+        Self {
+            origin,
+            u,
+            v,
+            normal,
+            d: normal.dot(&origin),
+            w: n / n.dot(&n),
+            area: n.length(),
+            material,
+        }
+    }
+}
+
+impl<T: Material> Hitable for Quad<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let denominator = self.normal.dot(&ray.direction);
+
+        // Ray is parallel to the plane
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(&ray.origin)) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let p = ray.point_at_parameter(t);
+        let planar = p - self.origin;
+        let alpha = self.w.dot(&planar.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar));
+
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        let mut hit_rec = HitRecord::new(t, p, self.normal, &self.material, (alpha, beta));
+        hit_rec.tangent = Some(self.u);
+        hit_rec.bitangent = Some(self.v);
+        hit_rec.set_face_normal(ray);
+
+        Some(hit_rec)
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        let corners = [
+            self.origin,
+            self.origin + self.u,
+            self.origin + self.v,
+            self.origin + self.u + self.v,
+        ];
+
+        let mut min = corners.iter().fold(Vec3::splat(f64::MAX), |a, &b| a.min(b));
+        let mut max = corners.iter().fold(Vec3::splat(f64::MIN), |a, &b| a.max(b));
+
+        let extent = max - min;
+        let pad = |e: f64| if e < BOX_PADDING { BOX_PADDING } else { 0.0 };
+        let padding = Vec3::new(pad(extent.x()), pad(extent.y()), pad(extent.z()));
+        min -= padding;
+        max += padding;
+
+        Some(Aabb::new(min, max))
+    }
+
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        let hit = match self.hit(ray, 0.001, f64::MAX) {
+            Some(v) => v,
+            None => return 0.0,
+        };
+
+        // Convert the uniform density over the area to one over solid angle
+        let direction = ray.direction;
+        let distance_squared = hit.t * hit.t * direction.sq_len();
+        let cosine = (direction.dot(&hit.normal) / direction.length()).abs();
+
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: Vec3, _time: f64, rng: &mut SmallRng) -> Vec3 {
+        let p = self.origin + self.u * rng.gen::<f64>() + self.v * rng.gen::<f64>();
+        p - origin
+    }
+}
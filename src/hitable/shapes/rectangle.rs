@@ -1,5 +1,7 @@
 use std::{marker::PhantomData, ops::RangeInclusive};
 
+use rand::{prelude::SmallRng, Rng};
+
 use crate::{
     hitable::{HitRecord, Hitable},
     materials::MaterialBuilder,
@@ -48,6 +50,10 @@ where
             &self.material,
             (u, v),
         );
+        hit_rec.tangent =
+            Some(Vec3::splat(0.0).set::<D1>(self.d1_range.end() - self.d1_range.start()));
+        hit_rec.bitangent =
+            Some(Vec3::splat(0.0).set::<D2>(self.d2_range.end() - self.d2_range.start()));
 
         hit_rec.set_face_normal(ray);
 
@@ -70,6 +76,27 @@ where
 
         Some(Aabb::new(min, max))
     }
+
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        let hit = match self.hit(ray, 0.001, f64::MAX) {
+            Some(v) => v,
+            None => return 0.0,
+        };
+
+        let area = (self.d1_range.end() - self.d1_range.start())
+            * (self.d2_range.end() - self.d2_range.start());
+        let distance_squared = hit.t * hit.t * ray.direction.sq_len();
+        let cosine = (ray.direction.get::<D3>() / ray.direction.length()).abs();
+
+        distance_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: Vec3, _time: f64, rng: &mut SmallRng) -> Vec3 {
+        let p = Vec3::splat(self.d3)
+            .set::<D1>(rng.gen_range(self.d1_range.clone()))
+            .set::<D2>(rng.gen_range(self.d2_range.clone()));
+        p - origin
+    }
 }
 
 // taken from, https://github.com/Globidev/toy-rt/blob/master/trt-core/src/hit/rect.rs#L74
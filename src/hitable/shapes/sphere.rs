@@ -1,5 +1,5 @@
 use crate::{
-    hitable::{HitRecord, Hitable},
+    hitable::{shapes::sphere_uv, HitInterval, HitRecord, Hitable},
     types::{Ray, Vec3},
     Aabb, Material,
 };
@@ -20,17 +20,24 @@ impl<T: Material + Clone + Sized> Sphere<T> {
         }
     }
 
-    /// p is a point on the sphere of radius 1 & center at origin
-    /// u is between [0,1]. Angle around Y axis from -X axis
-    /// v is between [0,1]. Angle from -Y to +Y axis
-    pub fn get_uv(p: Vec3) -> (f64, f64) {
-        let theta = (-p.y()).acos();
-        let phi = f64::atan2(-p.z(), p.x()) + std::f64::consts::PI;
+    fn hit_record(&self, ray: &Ray, t: f64) -> HitRecord {
+        let p = ray.point_at_parameter(t);
+        let normal = (p - self.center) / self.radius;
 
-        let u = phi / (2.0 * std::f64::consts::PI);
-        let v = theta / std::f64::consts::PI;
+        let mut hit_rec = HitRecord::new(t, p, normal, &self.material, sphere_uv(normal));
 
-        (u, v)
+        // u goes once around the Y axis and v from pole to pole, neither has
+        // a direction at the poles themselves
+        let around = Vec3::new(normal.z(), 0.0, -normal.x());
+        if around.sq_len() > 1e-12 {
+            let pi = std::f64::consts::PI;
+            hit_rec.tangent = Some(around * (2.0 * pi * self.radius));
+            hit_rec.bitangent = Some(normal.cross(&around.unit_vector()) * (pi * self.radius));
+        }
+
+        hit_rec.set_face_normal(ray);
+
+        hit_rec
     }
 }
 
@@ -56,18 +63,37 @@ impl<T: Material + Clone + Sized> Hitable for Sphere<T> {
                 root = (-b + discriminant_root) / a;
             }
             if root > t_min && root < t_max {
-                let p = ray.point_at_parameter(root);
-                let normal = (p - self.center) / self.radius;
+                return Some(self.hit_record(ray, root));
+            }
+        }
+        None
+    }
+
+    /// Both roots at once, instead of stepping through them with `hit`
+    fn hit_intervals(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitInterval> {
+        let oc = ray.origin - self.center;
+        let a = ray.direction.dot(&ray.direction);
+        let b = oc.dot(&ray.direction);
+        let c = oc.dot(&oc) - self.radius * self.radius;
 
-                let mut hit_rec =
-                    HitRecord::new(root, p, normal, &self.material, Self::get_uv(normal));
+        let discriminant = b * b - a * c;
+        if discriminant <= 0.0 {
+            return Vec::new();
+        }
 
-                hit_rec.set_face_normal(ray);
+        let discriminant_root = discriminant.sqrt();
+        let enter = (-b - discriminant_root) / a;
+        let exit = (-b + discriminant_root) / a;
 
-                return Some(hit_rec);
-            }
+        if exit <= t_min || enter >= t_max {
+            return Vec::new();
         }
-        None
+
+        // Outward normals face the ray where it enters, so front_face is already right
+        vec![HitInterval {
+            enter: (enter > t_min).then(|| self.hit_record(ray, enter)),
+            exit: (exit < t_max).then(|| self.hit_record(ray, exit)),
+        }]
     }
 
     fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
@@ -0,0 +1,193 @@
+use crate::{
+    hitable::{shapes::azimuth, HitRecord, Hitable},
+    types::{Ray, Vec3},
+    Aabb, Material,
+};
+
+/// Roots closer to zero than this are treated as zero by the polynomial solvers
+const EPSILON: f64 = 1e-9;
+
+/// A ring lying on the XZ plane around `center`.
+///
+/// `major_radius` is the distance from the center to the middle of the tube and
+/// `minor_radius` the radius of the tube. u goes around the ring like on a
+/// `Sphere` and v goes around the tube, starting from its inner side.
+#[derive(Clone)]
+pub struct Torus<T: Material> {
+    center: Vec3,
+    major_radius: f64,
+    minor_radius: f64,
+    material: T,
+}
+
+impl<T: Material> Torus<T> {
+    pub fn new(center: Vec3, major_radius: f64, minor_radius: f64, material: T) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+impl<T: Material> Hitable for Torus<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Solving with a unit direction relative to the center keeps the
+        // coefficients of the quartic in a sane range
+        let length = ray.direction.length();
+        let o = ray.origin - self.center;
+        let d = ray.direction / length;
+
+        let r2 = self.major_radius * self.major_radius;
+        let e = o.dot(&o) + r2 - self.minor_radius * self.minor_radius;
+        let f = o.dot(&d);
+
+        // (|p|^2 + R^2 - r^2)^2 = 4R^2 (x^2 + z^2) with p = o + t * d
+        let coefficients = [
+            e * e - 4.0 * r2 * (o.x() * o.x() + o.z() * o.z()),
+            4.0 * f * e - 8.0 * r2 * (o.x() * d.x() + o.z() * d.z()),
+            4.0 * f * f + 2.0 * e - 4.0 * r2 * (d.x() * d.x() + d.z() * d.z()),
+            4.0 * f,
+        ];
+
+        let t = solve_quartic(coefficients)
+            .into_iter()
+            .flatten()
+            .map(|t| t / length)
+            .filter(|&t| t > t_min && t < t_max)
+            .min_by(|a, b| a.total_cmp(b))?;
+
+        let p = o + ray.direction * t;
+        let s = p.dot(&p) + r2 - self.minor_radius * self.minor_radius;
+        let normal =
+            Vec3::new(p.x() * (s - 2.0 * r2), p.y() * s, p.z() * (s - 2.0 * r2)).unit_vector();
+
+        let distance_from_axis = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        let tube_angle = f64::atan2(p.y(), self.major_radius - distance_from_axis);
+        let uv = (
+            azimuth(p.x(), p.z()),
+            (tube_angle + std::f64::consts::PI) / (2.0 * std::f64::consts::PI),
+        );
+
+        let mut hit_rec = HitRecord::new(t, ray.point_at_parameter(t), normal, &self.material, uv);
+        hit_rec.set_face_normal(ray);
+
+        Some(hit_rec)
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        let outer = self.major_radius + self.minor_radius;
+        let extent = Vec3::new(outer, self.minor_radius, outer);
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+}
+
+/// Real roots of x^4 + c[3] x^3 + c[2] x^2 + c[1] x + c[0] = 0.
+/// Jochen Schwarze, Cubic and Quartic Roots, Graphics Gems 1990
+/// https://github.com/erich666/GraphicsGems/blob/master/gems/Roots3And4.c
+///
+/// Roots are polished with a couple of Newton steps since the closed form
+/// loses precision when the roots are far apart.
+fn solve_quartic([c0, c1, c2, c3]: [f64; 4]) -> [Option<f64>; 4] {
+    // Substitute x = y - c3 / 4 to remove the cubic term,
+    // y^4 + p y^2 + q y + r = 0
+    let sq_c3 = c3 * c3;
+    let p = -3.0 / 8.0 * sq_c3 + c2;
+    let q = 1.0 / 8.0 * sq_c3 * c3 - 1.0 / 2.0 * c3 * c2 + c1;
+    let r = -3.0 / 256.0 * sq_c3 * sq_c3 + 1.0 / 16.0 * sq_c3 * c2 - 1.0 / 4.0 * c3 * c1 + c0;
+
+    let mut roots = [None; 4];
+
+    if r.abs() < EPSILON {
+        // No absolute term, y (y^3 + p y + q) = 0
+        roots[0] = Some(0.0);
+        roots[1..].copy_from_slice(&solve_cubic([q, p, 0.0]));
+    } else {
+        // Solve the resolvent cubic and use one of its roots
+        // to split the quartic into two quadratics
+        let z = solve_cubic([1.0 / 2.0 * r * p - 1.0 / 8.0 * q * q, -r, -1.0 / 2.0 * p])[0]
+            .expect("cubics always have a real root");
+
+        let u = match z * z - r {
+            v if v.abs() < EPSILON => 0.0,
+            v if v > 0.0 => v.sqrt(),
+            _ => return roots,
+        };
+        let v = match 2.0 * z - p {
+            v if v.abs() < EPSILON => 0.0,
+            v if v > 0.0 => v.sqrt(),
+            _ => return roots,
+        };
+
+        let v = if q < 0.0 { -v } else { v };
+        roots[..2].copy_from_slice(&solve_quadratic([z - u, v]));
+        roots[2..].copy_from_slice(&solve_quadratic([z + u, -v]));
+    }
+
+    let f = |x: f64| (((x + c3) * x + c2) * x + c1) * x + c0;
+    let df = |x: f64| ((4.0 * x + 3.0 * c3) * x + 2.0 * c2) * x + c1;
+
+    roots.map(|root| {
+        root.map(|y| {
+            let mut x = y - 1.0 / 4.0 * c3;
+            for _ in 0..2 {
+                let slope = df(x);
+                if slope != 0.0 {
+                    x -= f(x) / slope;
+                }
+            }
+            x
+        })
+    })
+}
+
+/// Real roots of x^3 + c[2] x^2 + c[1] x + c[0] = 0, at least one of which always exists
+fn solve_cubic([c0, c1, c2]: [f64; 3]) -> [Option<f64>; 3] {
+    // Substitute x = y - c2 / 3 to remove the quadratic term, y^3 + 3 p y + 2 q = 0
+    let sq_c2 = c2 * c2;
+    let p = 1.0 / 3.0 * (-1.0 / 3.0 * sq_c2 + c1);
+    let q = 1.0 / 2.0 * (2.0 / 27.0 * c2 * sq_c2 - 1.0 / 3.0 * c2 * c1 + c0);
+
+    let cb_p = p * p * p;
+    let d = q * q + cb_p;
+
+    let roots = if d.abs() < EPSILON {
+        if q.abs() < EPSILON {
+            [Some(0.0), None, None]
+        } else {
+            let u = (-q).cbrt();
+            [Some(2.0 * u), Some(-u), None]
+        }
+    } else if d < 0.0 {
+        // Three real roots
+        let phi = 1.0 / 3.0 * (-q / (-cb_p).sqrt()).acos();
+        let t = 2.0 * (-p).sqrt();
+        [
+            Some(t * phi.cos()),
+            Some(-t * (phi + std::f64::consts::PI / 3.0).cos()),
+            Some(-t * (phi - std::f64::consts::PI / 3.0).cos()),
+        ]
+    } else {
+        // One real root
+        let sqrt_d = d.sqrt();
+        [Some((sqrt_d - q).cbrt() - (sqrt_d + q).cbrt()), None, None]
+    };
+
+    roots.map(|root| root.map(|y| y - 1.0 / 3.0 * c2))
+}
+
+/// Real roots of x^2 + c[1] x + c[0] = 0
+fn solve_quadratic([c0, c1]: [f64; 2]) -> [Option<f64>; 2] {
+    let p = c1 / 2.0;
+    let d = p * p - c0;
+
+    if d.abs() < EPSILON {
+        [Some(-p), None]
+    } else if d < 0.0 {
+        [None, None]
+    } else {
+        let sqrt_d = d.sqrt();
+        [Some(sqrt_d - p), Some(-sqrt_d - p)]
+    }
+}
@@ -0,0 +1,216 @@
+use crate::{
+    hitable::{HitRecord, Hitable},
+    types::{Ray, Vec3},
+    Aabb, Material,
+};
+
+/// Thickness given to the bounding box along any axis the triangle is flat in.
+/// Same padding `Rectangle` uses so axis aligned triangles still have a volume.
+const BOX_PADDING: f64 = 0.0001;
+
+#[derive(Clone)]
+pub struct Triangle<T: Material> {
+    vertices: [Vec3; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: [(f64, f64); 3],
+    material: T,
+}
+
+impl<T: Material> Triangle<T> {
+    /// Triangle with a flat normal and texture coordinates (0, 0), (1, 0) & (0, 1)
+    pub fn new(vertices: [Vec3; 3], material: T) -> Self {
+        Self {
+            vertices,
+            normals: None,
+            uvs: [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)],
+            material,
+        }
+    }
+
+    /// Per vertex normals, interpolated across the face for smooth shading
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Self {
+        self.normals = Some(normals);
+        self
+    }
+
+    /// Per vertex texture coordinates, interpolated across the face
+    pub fn with_uvs(mut self, uvs: [(f64, f64); 3]) -> Self {
+        self.uvs = uvs;
+        self
+    }
+}
+
+impl<T: Material> Hitable for Triangle<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t, barycentric) = intersect(&self.vertices, ray, t_min, t_max)?;
+
+        let normal = match self.normals {
+            Some(normals) => interpolate(&normals, barycentric).unit_vector(),
+            None => face_normal(&self.vertices),
+        };
+
+        let mut hit_rec = HitRecord::new(
+            t,
+            interpolate(&self.vertices, barycentric),
+            normal,
+            &self.material,
+            interpolate_uv(&self.uvs, barycentric),
+        );
+        if let Some((tangent, bitangent)) = tangents(&self.vertices, &self.uvs) {
+            hit_rec.tangent = Some(tangent);
+            hit_rec.bitangent = Some(bitangent);
+        }
+
+        hit_rec.set_face_normal(ray);
+
+        Some(hit_rec)
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        Some(bounding_box(&self.vertices))
+    }
+}
+
+/// Watertight ray/triangle intersection.
+/// Sven Woop, Carsten Benthin & Ingo Wald, Watertight Ray/Triangle Intersection
+/// https://jcgt.org/published/0002/01/05/paper.pdf
+///
+/// Rays hitting an edge or a vertex shared by two triangles always hit at least one of
+/// them, so meshes have no cracks for rays to slip through.
+/// Returns t along with the barycentric coordinates of the hit point.
+pub fn intersect(
+    vertices: &[Vec3; 3],
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, [f64; 3])> {
+    let dir = ray.direction;
+
+    // Pick the dimension where the ray direction is largest as z and
+    // swap x & y if needed to keep the winding of the triangle
+    let kz = max_dimension(dir);
+    let mut kx = (kz + 1) % 3;
+    let mut ky = (kx + 1) % 3;
+    if axis(dir, kz) < 0.0 {
+        std::mem::swap(&mut kx, &mut ky);
+    }
+
+    // Shear constants to transform the ray direction to +Z
+    let sx = axis(dir, kx) / axis(dir, kz);
+    let sy = axis(dir, ky) / axis(dir, kz);
+    let sz = 1.0 / axis(dir, kz);
+
+    // Vertices relative to the ray origin
+    let a = vertices[0] - ray.origin;
+    let b = vertices[1] - ray.origin;
+    let c = vertices[2] - ray.origin;
+
+    // Shear and scale the vertices
+    let shear = |v: Vec3| {
+        let z = axis(v, kz);
+        (axis(v, kx) - sx * z, axis(v, ky) - sy * z)
+    };
+    let (ax, ay) = shear(a);
+    let (bx, by) = shear(b);
+    let (cx, cy) = shear(c);
+
+    // Scaled barycentric coordinates
+    let u = cx * by - cy * bx;
+    let v = ax * cy - ay * cx;
+    let w = bx * ay - by * ax;
+
+    if (u < 0.0 || v < 0.0 || w < 0.0) && (u > 0.0 || v > 0.0 || w > 0.0) {
+        return None;
+    }
+
+    let det = u + v + w;
+    if det == 0.0 {
+        return None;
+    }
+
+    // Scaled z coordinates of the vertices give the scaled hit distance
+    let az = sz * axis(a, kz);
+    let bz = sz * axis(b, kz);
+    let cz = sz * axis(c, kz);
+    let t = (u * az + v * bz + w * cz) / det;
+
+    if t < t_min || t > t_max {
+        return None;
+    }
+
+    Some((t, [u / det, v / det, w / det]))
+}
+
+pub fn interpolate(values: &[Vec3; 3], barycentric: [f64; 3]) -> Vec3 {
+    values[0] * barycentric[0] + values[1] * barycentric[1] + values[2] * barycentric[2]
+}
+
+pub fn interpolate_uv(uvs: &[(f64, f64); 3], barycentric: [f64; 3]) -> (f64, f64) {
+    (
+        uvs[0].0 * barycentric[0] + uvs[1].0 * barycentric[1] + uvs[2].0 * barycentric[2],
+        uvs[0].1 * barycentric[0] + uvs[1].1 * barycentric[1] + uvs[2].1 * barycentric[2],
+    )
+}
+
+/// How the position changes with u and with v across the triangle, solved
+/// from the edges and the change in texture coordinates along them. They're
+/// only perpendicular to each other if the texture isn't sheared on the face.
+/// `None` if the texture coordinates don't span an area
+pub fn tangents(vertices: &[Vec3; 3], uvs: &[(f64, f64); 3]) -> Option<(Vec3, Vec3)> {
+    let (e1, e2) = (vertices[1] - vertices[0], vertices[2] - vertices[0]);
+    let (du1, dv1) = (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1);
+    let (du2, dv2) = (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1);
+
+    let determinant = du1 * dv2 - du2 * dv1;
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+
+    let tangent = (e1 * dv2 - e2 * dv1) / determinant;
+    let bitangent = (e2 * du1 - e1 * du2) / determinant;
+    if tangent.sq_len() == 0.0 || bitangent.sq_len() == 0.0 {
+        return None;
+    }
+
+    Some((tangent, bitangent))
+}
+
+pub fn face_normal(vertices: &[Vec3; 3]) -> Vec3 {
+    (vertices[1] - vertices[0])
+        .cross(&(vertices[2] - vertices[0]))
+        .unit_vector()
+}
+
+pub fn bounding_box(vertices: &[Vec3; 3]) -> Aabb {
+    let mut min = vertices[0].min(vertices[1]).min(vertices[2]);
+    let mut max = vertices[0].max(vertices[1]).max(vertices[2]);
+
+    // Pad out any axis the triangle is flat in
+    let extent = max - min;
+    let pad = |e: f64| if e < BOX_PADDING { BOX_PADDING } else { 0.0 };
+    let padding = Vec3::new(pad(extent.x()), pad(extent.y()), pad(extent.z()));
+    min -= padding;
+    max += padding;
+
+    Aabb::new(min, max)
+}
+
+fn max_dimension(v: Vec3) -> usize {
+    let (x, y, z) = (v.x().abs(), v.y().abs(), v.z().abs());
+
+    if x > y && x > z {
+        0
+    } else if y > z {
+        1
+    } else {
+        2
+    }
+}
+
+fn axis(v: Vec3, i: usize) -> f64 {
+    match i {
+        0 => v.x(),
+        1 => v.y(),
+        _ => v.z(),
+    }
+}
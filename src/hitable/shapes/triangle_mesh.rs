@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use crate::{
+    hitable::{
+        shapes::{
+            flat_bvh::FlatBvh,
+            triangle::{
+                bounding_box, face_normal, interpolate, interpolate_uv, intersect, tangents,
+            },
+        },
+        Accelerator, BuildStats, HitRecord, Hitable,
+    },
+    types::{Ray, Vec3},
+    Aabb, Material,
+};
+
+/// Vertex buffers of an indexed triangle mesh. Normals, uvs and colors are
+/// optional, when present there is one for every position.
+#[derive(Debug, Default, Clone)]
+pub struct MeshData {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<(f64, f64)>,
+    pub colors: Vec<Vec3>,
+    /// Indices into the vertex buffers, three for every triangle
+    pub indices: Vec<[u32; 3]>,
+}
+
+impl MeshData {
+    pub fn new(positions: Vec<Vec3>, indices: Vec<[u32; 3]>) -> Self {
+        Self {
+            positions,
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            colors: Vec::new(),
+            indices,
+        }
+    }
+
+    pub fn with_normals(mut self, normals: Vec<Vec3>) -> Self {
+        assert_eq!(normals.len(), self.positions.len(), "one normal per vertex");
+        self.normals = normals;
+        self
+    }
+
+    pub fn with_uvs(mut self, uvs: Vec<(f64, f64)>) -> Self {
+        assert_eq!(uvs.len(), self.positions.len(), "one uv per vertex");
+        self.uvs = uvs;
+        self
+    }
+
+    /// Per vertex colors, read through the `VertexColor` texture
+    pub fn with_colors(mut self, colors: Vec<Vec3>) -> Self {
+        assert_eq!(colors.len(), self.positions.len(), "one color per vertex");
+        self.colors = colors;
+        self
+    }
+
+    fn triangle(&self, i: u32) -> [Vec3; 3] {
+        let [a, b, c] = self.indices[i as usize];
+        [
+            self.positions[a as usize],
+            self.positions[b as usize],
+            self.positions[c as usize],
+        ]
+    }
+}
+
+/// An indexed triangle mesh hit as a single object.
+///
+/// The vertex buffers are behind an `Arc` so several meshes with different
+/// materials can share them. Triangles are found through a BVH over triangle
+/// indices kept inside the mesh, so there is one `Hitable` for the whole mesh
+/// no matter how many triangles it has.
+pub struct TriangleMesh<T: Material> {
+    data: Arc<MeshData>,
+    bvh: FlatBvh,
+    material: T,
+}
+
+impl<T: Material> TriangleMesh<T> {
+    pub fn new(data: impl Into<Arc<MeshData>>, material: T) -> Self {
+        let data = data.into();
+        assert!(!data.indices.is_empty(), "mesh has no triangles");
+
+        let boxes = (0..data.indices.len() as u32)
+            .map(|i| bounding_box(&data.triangle(i)))
+            .collect::<Vec<_>>();
+
+        Self {
+            bvh: FlatBvh::new(&boxes),
+            data,
+            material,
+        }
+    }
+}
+
+impl<T: Material> Hitable for TriangleMesh<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut closest = None;
+        self.bvh
+            .traverse(ray, t_min, t_max, |triangle, closest_so_far| {
+                let (t, barycentric) =
+                    intersect(&self.data.triangle(triangle), ray, t_min, closest_so_far)?;
+                closest = Some((triangle, t, barycentric));
+                Some(t)
+            });
+
+        let (triangle, t, barycentric) = closest?;
+        let [a, b, c] = self.data.indices[triangle as usize];
+        let (a, b, c) = (a as usize, b as usize, c as usize);
+        let vertices = self.data.triangle(triangle);
+
+        let normal = if self.data.normals.is_empty() {
+            face_normal(&vertices)
+        } else {
+            let normals = &self.data.normals;
+            interpolate(&[normals[a], normals[b], normals[c]], barycentric).unit_vector()
+        };
+
+        let uvs = if self.data.uvs.is_empty() {
+            [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]
+        } else {
+            let uvs = &self.data.uvs;
+            [uvs[a], uvs[b], uvs[c]]
+        };
+
+        let mut hit_rec = HitRecord::new(
+            t,
+            interpolate(&vertices, barycentric),
+            normal,
+            &self.material,
+            interpolate_uv(&uvs, barycentric),
+        );
+        if let Some((tangent, bitangent)) = tangents(&vertices, &uvs) {
+            hit_rec.tangent = Some(tangent);
+            hit_rec.bitangent = Some(bitangent);
+        }
+        hit_rec.set_face_normal(ray);
+
+        if !self.data.colors.is_empty() {
+            let colors = &self.data.colors;
+            hit_rec.color = Some(interpolate(&[colors[a], colors[b], colors[c]], barycentric));
+        }
+
+        Some(hit_rec)
+    }
+
+    fn bounding_box(&self, _t0: f64, _t1: f64) -> Option<Aabb> {
+        Some(self.bvh.bbox())
+    }
+}
+
+impl<T: Material> Accelerator for TriangleMesh<T> {
+    fn stats(&self) -> BuildStats {
+        self.bvh.stats()
+    }
+}
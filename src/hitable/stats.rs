@@ -0,0 +1,113 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Relative cost of visiting a node and of testing a primitive
+/// used in the surface area heuristic
+const TRAVERSAL_COST: f64 = 1.0;
+const INTERSECTION_COST: f64 = 1.0;
+
+/// Accelerators that can describe the tree they built
+pub trait Accelerator {
+    fn stats(&self) -> BuildStats;
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct BuildStats {
+    pub nodes: usize,
+    pub depth: usize,
+    /// leaf_sizes[n] is the number of nodes with n objects directly under them
+    pub leaf_sizes: Vec<usize>,
+    /// Expected cost of tracing a random ray that hits the root box
+    pub sah_cost: f64,
+}
+
+impl BuildStats {
+    /// `relative_area` is the surface area of the node divided by the surface area of the root
+    pub fn add_node(&mut self, depth: usize, leaf_size: usize, relative_area: f64) {
+        self.nodes += 1;
+        self.depth = self.depth.max(depth);
+
+        if self.leaf_sizes.len() <= leaf_size {
+            self.leaf_sizes.resize(leaf_size + 1, 0);
+        }
+        self.leaf_sizes[leaf_size] += 1;
+
+        self.sah_cost += relative_area * (TRAVERSAL_COST + INTERSECTION_COST * leaf_size as f64);
+    }
+}
+
+impl Display for BuildStats {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "Nodes = {} Depth = {} SAH Cost = {:.2} Leaf Sizes =",
+            self.nodes, self.depth, self.sah_cost
+        )?;
+
+        for (size, count) in self.leaf_sizes.iter().enumerate() {
+            write!(f, " {}:{}", size, count)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Number of box and primitive tests performed on this thread since the last
+/// call to `take_counters`. Only collected with the `stats` feature enabled.
+#[cfg(feature = "stats")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RayCounters {
+    pub box_tests: u64,
+    pub primitive_tests: u64,
+}
+
+#[cfg(feature = "stats")]
+impl std::ops::AddAssign for RayCounters {
+    fn add_assign(&mut self, o: RayCounters) {
+        self.box_tests += o.box_tests;
+        self.primitive_tests += o.primitive_tests;
+    }
+}
+
+#[cfg(feature = "stats")]
+mod counters {
+    use std::cell::Cell;
+
+    use super::RayCounters;
+
+    thread_local! {
+        static COUNTERS: Cell<RayCounters> = Cell::new(RayCounters::default());
+    }
+
+    #[inline]
+    pub fn count_box_tests(n: u64) {
+        COUNTERS.with(|c| {
+            let mut counters = c.get();
+            counters.box_tests += n;
+            c.set(counters);
+        });
+    }
+
+    #[inline]
+    pub fn count_primitive_test() {
+        COUNTERS.with(|c| {
+            let mut counters = c.get();
+            counters.primitive_tests += 1;
+            c.set(counters);
+        });
+    }
+
+    pub fn take_counters() -> RayCounters {
+        COUNTERS.with(|c| c.take())
+    }
+}
+
+#[cfg(not(feature = "stats"))]
+mod counters {
+    #[inline(always)]
+    pub fn count_box_tests(_n: u64) {}
+
+    #[inline(always)]
+    pub fn count_primitive_test() {}
+}
+
+pub use counters::*;
@@ -0,0 +1,149 @@
+use rand::prelude::SmallRng;
+
+use crate::{
+    hitable::{HitInterval, HitRecord, Hitable},
+    types::{Mat4, Ray, Vec3},
+    Aabb,
+};
+
+/// An object placed with an affine transform, so it can be scaled, sheared and
+/// rotated around any axis as well as moved.
+///
+/// Rays are taken into the space of the object with the inverse matrix. The
+/// direction isn't normalized after that so t is the same in both spaces.
+/// Normals go back with the inverse transpose, which keeps them perpendicular
+/// to the surface under non uniform scales. Transforming a `Transform` again
+/// multiplies the matrices instead of adding another wrapper
+pub struct Transform<T> {
+    object: T,
+    matrix: Mat4,
+    inverse: Mat4,
+    normal_matrix: Mat4,
+}
+
+impl<T: Hitable> Transform<T> {
+    /// Panics if `matrix` can't be inverted
+    pub fn new(object: T, matrix: Mat4) -> Self {
+        let inverse = matrix
+            .inverse()
+            .expect("transform matrix is not invertible");
+
+        Self {
+            object,
+            matrix,
+            inverse,
+            normal_matrix: inverse.transpose(),
+        }
+    }
+
+    /// Applies `matrix` after the current transform
+    pub fn transform(self, matrix: Mat4) -> Self {
+        Self::new(self.object, matrix * self.matrix)
+    }
+
+    pub fn translate(self, offset: impl Into<Vec3>) -> Self {
+        self.transform(Mat4::translation(offset.into()))
+    }
+
+    pub fn scale(self, factor: impl Into<Vec3>) -> Self {
+        self.transform(Mat4::scaling(factor.into()))
+    }
+
+    pub fn rotate_axis(self, axis: impl Into<Vec3>, angle: f64) -> Self {
+        self.transform(Mat4::rotation(axis.into(), angle))
+    }
+}
+
+impl<T: Hitable> Hitable for Transform<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut hit = self
+            .object
+            .hit(&to_object(&self.inverse, ray), t_min, t_max)?;
+        to_world(&self.matrix, &self.normal_matrix, &mut hit);
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self, t0: f64, t1: f64) -> Option<Aabb> {
+        let bbox = self.object.bounding_box(t0, t1)?;
+        Some(transform_box(&self.matrix, &bbox))
+    }
+
+    fn hit_intervals(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitInterval> {
+        let mut intervals = self
+            .object
+            .hit_intervals(&to_object(&self.inverse, ray), t_min, t_max);
+        for interval in intervals.iter_mut() {
+            for hit in interval.enter.iter_mut().chain(interval.exit.iter_mut()) {
+                to_world(&self.matrix, &self.normal_matrix, hit);
+            }
+        }
+
+        intervals
+    }
+
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        let (object_ray, stretch) = to_object_pdf(&self.inverse, ray);
+        self.object.pdf_value(&object_ray) * stretch
+    }
+
+    fn random(&self, origin: Vec3, time: f64, rng: &mut SmallRng) -> Vec3 {
+        let direction = self
+            .object
+            .random(self.inverse.transform_point(origin), time, rng);
+        self.matrix.transform_vector(direction)
+    }
+}
+
+/// Takes a ray into the space of an object, given the inverse of its transform
+pub(super) fn to_object(inverse: &Mat4, ray: &Ray) -> Ray {
+    Ray::new(
+        inverse.transform_point(ray.origin),
+        inverse.transform_vector(ray.direction),
+        ray.time(),
+    )
+}
+
+/// Takes a ray into the space of an object for looking up the density of its
+/// direction, along with how much the transform squeezes solid angle around it.
+/// For a unit direction w that's |det A| / |A w|^3, A being the inverse
+pub(super) fn to_object_pdf(inverse: &Mat4, ray: &Ray) -> (Ray, f64) {
+    let direction = inverse.transform_vector(ray.direction.unit_vector());
+    let stretch = inverse.determinant().abs() / direction.length().powi(3);
+
+    (
+        Ray::new(inverse.transform_point(ray.origin), direction, ray.time()),
+        stretch,
+    )
+}
+
+/// Moves a hit found in object space to world space, `normal_matrix` being the
+/// transpose of the inverse. The sign of the dot product between the normal and
+/// the ray survives the transform, so `front_face` stays valid and the normal
+/// keeps facing against the ray
+pub(super) fn to_world(matrix: &Mat4, normal_matrix: &Mat4, hit: &mut HitRecord) {
+    hit.p = matrix.transform_point(hit.p);
+    hit.normal = normal_matrix.transform_vector(hit.normal).unit_vector();
+    hit.tangent = hit.tangent.map(|tangent| matrix.transform_vector(tangent));
+    hit.bitangent = hit
+        .bitangent
+        .map(|bitangent| matrix.transform_vector(bitangent));
+}
+
+/// Box around the transformed corners of `bbox`
+pub(super) fn transform_box(matrix: &Mat4, bbox: &Aabb) -> Aabb {
+    let mut min = Vec3::splat(f64::MAX);
+    let mut max = Vec3::splat(f64::MIN);
+
+    for x in [bbox.min.x(), bbox.max.x()] {
+        for y in [bbox.min.y(), bbox.max.y()] {
+            for z in [bbox.min.z(), bbox.max.z()] {
+                let corner = matrix.transform_point(Vec3::new(x, y, z));
+                min = min.min(corner);
+                max = max.max(corner);
+            }
+        }
+    }
+
+    Aabb::new(min, max)
+}
@@ -1,6 +1,8 @@
+use rand::prelude::SmallRng;
+
 use crate::{
-    hitable::{HitRecord, Hitable},
-    types::{Ray, Vec3},
+    hitable::{HitInterval, HitRecord, Hitable},
+    types::{OffsetRay, Ray, Vec3},
     Aabb,
 };
 
@@ -17,7 +19,13 @@ impl<T> Translate<T> {
 
 impl<T: Hitable> Hitable for Translate<T> {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let moved_ray = Ray::new(ray.origin - self.offset, ray.direction, ray.time());
+        let moved_ray = Ray::new(ray.origin - self.offset, ray.direction, ray.time())
+            .with_differentials(ray.differentials.map(|differentials| {
+                differentials.map(|offset| OffsetRay {
+                    origin: offset.origin - self.offset,
+                    ..offset
+                })
+            }));
 
         if let Some(mut hit) = self.object.hit(&moved_ray, t_min, t_max) {
             hit.p += self.offset;
@@ -34,4 +42,26 @@ impl<T: Hitable> Hitable for Translate<T> {
             .bounding_box(t0, t1)
             .map(|bbox| Aabb::new(bbox.min + self.offset, bbox.max + self.offset))
     }
+
+    fn pdf_value(&self, ray: &Ray) -> f64 {
+        let moved_ray = Ray::new(ray.origin - self.offset, ray.direction, ray.time());
+        self.object.pdf_value(&moved_ray)
+    }
+
+    fn random(&self, origin: Vec3, time: f64, rng: &mut SmallRng) -> Vec3 {
+        self.object.random(origin - self.offset, time, rng)
+    }
+
+    fn hit_intervals(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitInterval> {
+        let moved_ray = Ray::new(ray.origin - self.offset, ray.direction, ray.time());
+
+        let mut intervals = self.object.hit_intervals(&moved_ray, t_min, t_max);
+        for interval in intervals.iter_mut() {
+            for hit in interval.enter.iter_mut().chain(interval.exit.iter_mut()) {
+                hit.p += self.offset;
+            }
+        }
+
+        intervals
+    }
 }
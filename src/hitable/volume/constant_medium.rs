@@ -54,6 +54,10 @@ impl<A: Hitable, B: Material> Hitable for ConstantMedium<A, B> {
             // Arbitrary
             front_face: true,
             normal: Vec3::new(1.0, 0.0, 0.0),
+            color: None,
+            tangent: None,
+            bitangent: None,
+            footprint: None,
         })
     }
 
@@ -0,0 +1,464 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ::gltf::{
+    buffer,
+    camera::Projection,
+    image::Source as ImageSource,
+    mesh::Mode,
+    texture::{MagFilter, MinFilter, Sampler, WrappingMode},
+    Gltf, Node,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::error::ImageError;
+
+use crate::{
+    hitable::shapes::{MeshData, TriangleMesh},
+    materials::{Dielectric, DiffuseLight, Lambertian, Metal, NormalMapped},
+    texture::{
+        ColorSpace, Filter, ImageTexture, Solid, TextureCache, Tinted, VertexColor, WrapMode,
+    },
+    types::{Mat4, Vec3},
+    Camera, Material,
+};
+
+#[derive(Debug)]
+pub enum GltfError {
+    Gltf(::gltf::Error),
+    Io { path: PathBuf, error: io::Error },
+    Base64(base64::DecodeError),
+    Texture { image: usize, error: ImageError },
+    Data(String),
+}
+
+impl Display for GltfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            GltfError::Gltf(e) => write!(f, "invalid glTF: {}", e),
+            GltfError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            GltfError::Base64(e) => write!(f, "invalid base64 data uri: {}", e),
+            GltfError::Texture { image, error } => {
+                write!(f, "error in decoding image {}: {}", image, error)
+            }
+            GltfError::Data(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for GltfError {}
+
+/// Everything in the default scene of a glTF file, with every node's
+/// transform baked into the vertices of its meshes
+pub struct GltfScene {
+    /// One mesh for every triangle primitive
+    pub meshes: Vec<TriangleMesh<Arc<dyn Material>>>,
+    pub cameras: Vec<GltfCamera>,
+}
+
+/// A perspective camera placed by its node in the scene
+#[derive(Debug, Copy, Clone)]
+pub struct GltfCamera {
+    pub look_from: Vec3,
+    pub look_at: Vec3,
+    pub v_up: Vec3,
+    /// In degrees
+    pub vertical_fov: f64,
+}
+
+impl GltfCamera {
+    /// glTF cameras are pinholes, so there is no defocus blur
+    pub fn camera(&self, aspect_ratio: f64) -> Camera {
+        Camera::new(
+            self.look_from,
+            self.look_at,
+            self.v_up,
+            self.vertical_fov,
+            aspect_ratio,
+            0.0,
+            1.0,
+            0.0,
+            1.0,
+        )
+    }
+}
+
+/// Loads a .gltf or .glb file along with any buffers and images it references.
+///
+/// Metallic-roughness materials are mapped to the closest material the
+/// renderer has. Emissive materials become a `DiffuseLight`, transmissive ones
+/// a `Dielectric`, metals a `Metal` using the base color factor and everything
+/// else a `Lambertian` with the base color texture, factor and vertex colors.
+/// Normal and occlusion textures are not used. Point and line primitives are
+/// skipped, as are orthographic cameras.
+pub fn load_gltf(path: impl AsRef<Path>) -> Result<GltfScene, GltfError> {
+    let path = path.as_ref();
+    let gltf = open(path)?;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let buffers = gltf
+        .buffers()
+        .map(|buffer| match buffer.source() {
+            buffer::Source::Bin => gltf
+                .blob
+                .clone()
+                .ok_or_else(|| GltfError::Data("glb has no binary chunk".to_string())),
+            buffer::Source::Uri(uri) => read_uri(directory, uri),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut images: HashMap<ImageKey, ImageTexture> = HashMap::new();
+    let mut materials: HashMap<(Option<usize>, bool), Arc<dyn Material>> = HashMap::new();
+    let mut meshes = Vec::new();
+
+    for (node, transform) in nodes(&gltf) {
+        let mesh = match node.mesh() {
+            Some(v) => v,
+            None => continue,
+        };
+
+        for primitive in mesh.primitives() {
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let error = |message: &str| {
+                GltfError::Data(format!(
+                    "mesh {} primitive {}: {}",
+                    mesh.index(),
+                    primitive.index(),
+                    message
+                ))
+            };
+
+            let positions = reader
+                .read_positions()
+                .ok_or_else(|| error("no vertex positions"))?
+                .map(|p| transform.transform_point(to_vec3(p)))
+                .collect::<Vec<_>>();
+
+            let vertices = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect::<Vec<_>>(),
+                None => (0..positions.len() as u32).collect(),
+            };
+            if let Some(&index) = vertices.iter().find(|&&i| i as usize >= positions.len()) {
+                return Err(error(&format!("vertex index {} out of range", index)));
+            }
+
+            let indices = match primitive.mode() {
+                Mode::Triangles => vertices
+                    .chunks_exact(3)
+                    .map(|v| [v[0], v[1], v[2]])
+                    .collect::<Vec<_>>(),
+                // Every other triangle of a strip is wound the other way
+                Mode::TriangleStrip => (2..vertices.len())
+                    .map(|i| match i % 2 {
+                        0 => [vertices[i - 2], vertices[i - 1], vertices[i]],
+                        _ => [vertices[i - 1], vertices[i - 2], vertices[i]],
+                    })
+                    .collect(),
+                Mode::TriangleFan => (2..vertices.len())
+                    .map(|i| [vertices[0], vertices[i - 1], vertices[i]])
+                    .collect(),
+                Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => continue,
+            };
+            if indices.is_empty() {
+                continue;
+            }
+
+            let mut data = MeshData::new(positions, indices);
+            if let Some(normals) = reader.read_normals() {
+                // Normals transform with the inverse transpose
+                let normal_matrix = transform.inverse().unwrap_or(Mat4::IDENTITY).transpose();
+                data = data.with_normals(
+                    normals
+                        .map(|n| normal_matrix.transform_vector(to_vec3(n)).unit_vector())
+                        .collect(),
+                );
+            }
+            if let Some(uvs) = reader.read_tex_coords(0) {
+                // glTF puts the origin of textures at the top left
+                data = data.with_uvs(
+                    uvs.into_f32()
+                        .map(|[u, v]| (u as f64, 1.0 - v as f64))
+                        .collect(),
+                );
+            }
+            if let Some(colors) = reader.read_colors(0) {
+                data = data.with_colors(
+                    colors
+                        .into_rgb_f32()
+                        .map(|[r, g, b]| Vec3::new(r, g, b))
+                        .collect(),
+                );
+            }
+
+            let material = primitive.material();
+            let has_colors = !data.colors.is_empty();
+            let material = match materials.get(&(material.index(), has_colors)) {
+                Some(v) => v.clone(),
+                None => {
+                    let converted =
+                        convert_material(&material, has_colors, &buffers, directory, &mut images)?;
+                    materials.insert((material.index(), has_colors), converted.clone());
+                    converted
+                }
+            };
+
+            meshes.push(TriangleMesh::new(data, material));
+        }
+    }
+
+    Ok(GltfScene {
+        meshes,
+        cameras: cameras(&gltf),
+    })
+}
+
+fn open(path: &Path) -> Result<Gltf, GltfError> {
+    Gltf::open(path).map_err(|e| match e {
+        ::gltf::Error::Io(error) => GltfError::Io {
+            path: path.to_path_buf(),
+            error,
+        },
+        e => GltfError::Gltf(e),
+    })
+}
+
+/// Every node of the default scene, or the first one if there is no default,
+/// along with its transform relative to the scene root
+fn nodes(gltf: &Gltf) -> Vec<(Node, Mat4)> {
+    fn visit<'a>(node: Node<'a>, parent: &Mat4, out: &mut Vec<(Node<'a>, Mat4)>) {
+        let local = Mat4::from_columns(node.transform().matrix().map(|c| c.map(|v| v as f64)));
+        let transform = *parent * local;
+
+        for child in node.children() {
+            visit(child, &transform, out);
+        }
+        out.push((node, transform));
+    }
+
+    let mut out = Vec::new();
+    if let Some(scene) = gltf.default_scene().or_else(|| gltf.scenes().next()) {
+        for node in scene.nodes() {
+            visit(node, &Mat4::IDENTITY, &mut out);
+        }
+    }
+
+    out
+}
+
+fn cameras(gltf: &Gltf) -> Vec<GltfCamera> {
+    nodes(gltf)
+        .into_iter()
+        .filter_map(|(node, transform)| match node.camera()?.projection() {
+            Projection::Perspective(perspective) => {
+                // Cameras look down -Z with +Y up in their own space
+                let look_from = transform.transform_point(Vec3::splat(0.0));
+                let forward = transform
+                    .transform_vector(Vec3::new(0.0, 0.0, -1.0))
+                    .unit_vector();
+
+                Some(GltfCamera {
+                    look_from,
+                    look_at: look_from + forward,
+                    v_up: transform
+                        .transform_vector(Vec3::new(0.0, 1.0, 0.0))
+                        .unit_vector(),
+                    vertical_fov: (perspective.yfov() as f64).to_degrees(),
+                })
+            }
+            Projection::Orthographic(_) => None,
+        })
+        .collect()
+}
+
+/// Decoded images are shared by every material sampling them, whatever the
+/// sampler, so the key is only the image and how its values are encoded
+type ImageKey = (usize, ColorSpace);
+
+fn convert_material(
+    material: &::gltf::Material,
+    has_colors: bool,
+    buffers: &[Vec<u8>],
+    directory: &Path,
+    images: &mut HashMap<ImageKey, ImageTexture>,
+) -> Result<Arc<dyn Material>, GltfError> {
+    // Color textures are sRGB encoded and the rest hold linear data, an image
+    // used both ways is decoded once for each
+    let mut texture =
+        |texture: Option<::gltf::Texture>, color_space: ColorSpace| -> Result<_, GltfError> {
+            let texture = match texture {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+            let image = texture.source();
+            let sampler = texture.sampler();
+
+            let key = (image.index(), color_space);
+            let decoded = match images.get(&key) {
+                Some(v) => v.clone(),
+                None => {
+                    let decoded = match image.source() {
+                        // Files go through the cache so other scenes using them
+                        // share the texels too
+                        ImageSource::Uri { uri, .. } if !uri.starts_with("data:") => {
+                            TextureCache::load(directory.join(percent_decode(uri)), color_space)
+                        }
+                        ImageSource::Uri { uri, .. } => {
+                            ImageTexture::from_bytes(&read_uri(directory, uri)?, color_space)
+                        }
+                        ImageSource::View { view, .. } => {
+                            let start = view.offset();
+                            let bytes = buffers[view.buffer().index()]
+                                .get(start..start + view.length())
+                                .ok_or_else(|| {
+                                    GltfError::Data("image view out of range".to_string())
+                                })?;
+                            ImageTexture::from_bytes(bytes, color_space)
+                        }
+                    }
+                    .map_err(|error| GltfError::Texture {
+                        image: image.index(),
+                        error,
+                    })?;
+                    images.insert(key, decoded.clone());
+                    decoded
+                }
+            };
+
+            // Copies share the texels, each sampler only sets how they're read
+            Ok(Some(
+                decoded
+                    .with_wrap(wrap_mode(sampler.wrap_s()), wrap_mode(sampler.wrap_t()))
+                    .with_filter(filter(&sampler)),
+            ))
+        };
+
+    let emissive = Vec3::new(
+        material.emissive_factor()[0],
+        material.emissive_factor()[1],
+        material.emissive_factor()[2],
+    ) * material.emissive_strength().unwrap_or(1.0) as f64;
+
+    if emissive.max_element(0.0) > 0.0 {
+        return Ok(
+            match texture(
+                material.emissive_texture().map(|info| info.texture()),
+                ColorSpace::Srgb,
+            )? {
+                Some(v) => Arc::new(DiffuseLight::new(Tinted::new(v, emissive))),
+                None => Arc::new(DiffuseLight::new(Solid::new(emissive))),
+            },
+        );
+    }
+
+    let transmission = material
+        .transmission()
+        .map_or(0.0, |t| t.transmission_factor());
+
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, _] = pbr.base_color_factor();
+    let base_color = Vec3::new(r, g, b);
+
+    let surface: Arc<dyn Material> = if transmission > 0.0 {
+        Arc::new(Dielectric::new(material.ior().unwrap_or(1.5) as f64))
+    } else if pbr.metallic_factor() >= 0.5 {
+        Arc::new(Metal::with_fuzz(base_color, pbr.roughness_factor() as f64))
+    } else {
+        match texture(
+            pbr.base_color_texture().map(|info| info.texture()),
+            ColorSpace::Srgb,
+        )? {
+            Some(v) => Arc::new(Lambertian::new(Tinted::new(v, base_color))),
+            None if has_colors => Arc::new(Lambertian::new(Tinted::new(
+                VertexColor::new(Vec3::splat(1.0)),
+                base_color,
+            ))),
+            None => Arc::new(Lambertian::new(Solid::new(base_color))),
+        }
+    };
+
+    // glTF normal maps have +Y pointing up the image, which is the way v
+    // increases once it's flipped on loading
+    let normals = material.normal_texture();
+    let strength = normals.as_ref().map_or(1.0, |n| n.scale() as f64);
+    Ok(
+        match texture(normals.map(|n| n.texture()), ColorSpace::Linear)? {
+            Some(v) => Arc::new(NormalMapped::new(surface, v, strength)),
+            None => surface,
+        },
+    )
+}
+
+/// Reads a buffer or image from a data uri or from a file relative to the scene
+fn read_uri(directory: &Path, uri: &str) -> Result<Vec<u8>, GltfError> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, data) = data
+            .split_once(";base64,")
+            .ok_or_else(|| GltfError::Data("only base64 data uris are supported".to_string()))?;
+        return STANDARD.decode(data).map_err(GltfError::Base64);
+    }
+
+    let path = directory.join(percent_decode(uri));
+    fs::read(&path).map_err(|error| GltfError::Io { path, error })
+}
+
+/// Uris escape reserved and non ascii bytes as %XX. Malformed escapes are kept as is
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn wrap_mode(mode: WrappingMode) -> WrapMode {
+    match mode {
+        WrappingMode::Repeat => WrapMode::Repeat,
+        WrappingMode::MirroredRepeat => WrapMode::Mirror,
+        WrappingMode::ClampToEdge => WrapMode::Clamp,
+    }
+}
+
+/// A nearest magnification filter is kept for textures meant to look blocky up
+/// close. Otherwise mipmapped minification filters get the mip pyramid
+fn filter(sampler: &Sampler) -> Filter {
+    match (sampler.mag_filter(), sampler.min_filter()) {
+        (Some(MagFilter::Nearest), _) => Filter::Nearest,
+        (
+            _,
+            Some(
+                MinFilter::NearestMipmapNearest
+                | MinFilter::LinearMipmapNearest
+                | MinFilter::NearestMipmapLinear
+                | MinFilter::LinearMipmapLinear,
+            ),
+        ) => Filter::Trilinear,
+        _ => Filter::Bilinear,
+    }
+}
+
+fn to_vec3([x, y, z]: [f32; 3]) -> Vec3 {
+    Vec3::new(x, y, z)
+}
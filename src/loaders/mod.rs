@@ -0,0 +1,8 @@
+mod gltf;
+mod mtl;
+mod obj;
+mod ply;
+
+pub use self::gltf::{load_gltf, GltfCamera, GltfScene};
+pub use obj::load_obj;
+pub use ply::load_ply;
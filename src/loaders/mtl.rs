@@ -0,0 +1,139 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    loaders::obj::{parse_f64, parse_name, parse_vec3, ObjError},
+    materials::{Dielectric, DiffuseLight, Lambertian, Metal},
+    texture::{ColorSpace, Solid, TextureCache},
+    types::Vec3,
+    Material,
+};
+
+/// Material parameters as written in a .mtl file
+struct MtlEntry {
+    kd: Vec3,
+    ks: Vec3,
+    ns: f64,
+    ni: Option<f64>,
+    d: f64,
+    ke: Vec3,
+    map_kd: Option<PathBuf>,
+}
+
+impl Default for MtlEntry {
+    fn default() -> Self {
+        Self {
+            kd: Vec3::splat(0.8),
+            ks: Vec3::splat(0.0),
+            ns: 0.0,
+            ni: None,
+            d: 1.0,
+            ke: Vec3::splat(0.0),
+            map_kd: None,
+        }
+    }
+}
+
+impl MtlEntry {
+    /// Picks the closest material the renderer has.
+    ///
+    /// Anything emissive becomes a `DiffuseLight`, anything see through a
+    /// `Dielectric` and anything where the specular color outweighs the diffuse
+    /// color a `Metal`, with the fuzz derived from the `Ns` exponent.
+    /// Everything else is `Lambertian`, textured with `map_Kd` when present.
+    fn into_material(self) -> Result<Arc<dyn Material>, ObjError> {
+        if self.ke.max_element(0.0) > 0.0 {
+            return Ok(Arc::new(DiffuseLight::new(Solid::new(self.ke))));
+        }
+
+        if self.d < 1.0 {
+            return Ok(Arc::new(Dielectric::new(self.ni.unwrap_or(1.5))));
+        }
+
+        if self.ks.max_element(0.0) > self.kd.max_element(0.0) {
+            // Phong exponent to roughness, Walter et al. 2007. Negative
+            // exponents are meaningless and would make the square root NaN
+            let fuzz = (2.0 / (self.ns.max(0.0) + 2.0)).sqrt();
+            return Ok(Arc::new(Metal::with_fuzz(self.ks, fuzz)));
+        }
+
+        match self.map_kd {
+            Some(path) => {
+                let texture = TextureCache::load(&path, ColorSpace::Srgb)
+                    .map_err(|error| ObjError::Texture { path, error })?;
+                Ok(Arc::new(Lambertian::new(texture)))
+            }
+            None => Ok(Arc::new(Lambertian::new(Solid::new(self.kd)))),
+        }
+    }
+}
+
+/// Reads every material in a .mtl file. Texture paths are relative to the file
+pub fn load_mtl(path: &Path) -> Result<HashMap<String, Arc<dyn Material>>, ObjError> {
+    let source = fs::read_to_string(path).map_err(|error| ObjError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut entries = Vec::new();
+    let mut current: Option<(String, MtlEntry)> = None;
+
+    for (i, line) in source.lines().enumerate() {
+        let error = |message: String| ObjError::Parse {
+            path: path.to_path_buf(),
+            line: i + 1,
+            message,
+        };
+
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(v) => v,
+            None => continue,
+        };
+
+        if keyword == "newmtl" {
+            let name = parse_name(line, keyword)
+                .ok_or_else(|| error("newmtl without a name".to_string()))?;
+            entries.extend(current.take());
+            current = Some((name.to_string(), MtlEntry::default()));
+            continue;
+        }
+
+        let entry = match current.as_mut() {
+            Some((_, entry)) => entry,
+            // Statements before the first newmtl have nothing to apply to
+            None => continue,
+        };
+
+        match keyword {
+            "Kd" => entry.kd = parse_vec3(tokens).map_err(error)?,
+            "Ks" => entry.ks = parse_vec3(tokens).map_err(error)?,
+            "Ke" => entry.ke = parse_vec3(tokens).map_err(error)?,
+            "Ns" => entry.ns = parse_f64(tokens.next()).map_err(error)?,
+            "Ni" => entry.ni = Some(parse_f64(tokens.next()).map_err(error)?),
+            "d" => entry.d = parse_f64(tokens.next()).map_err(error)?,
+            "Tr" => entry.d = 1.0 - parse_f64(tokens.next()).map_err(error)?,
+            "map_Kd" => {
+                // Texture options come before the file name, which is always last
+                let file = tokens
+                    .last()
+                    .ok_or_else(|| error("map_Kd without a file name".to_string()))?;
+                entry.map_kd = Some(directory.join(file));
+            }
+            // Everything else has no equivalent in the renderer
+            _ => (),
+        }
+    }
+    entries.extend(current);
+
+    entries
+        .into_iter()
+        .map(|(name, entry)| Ok((name, entry.into_material()?)))
+        .collect()
+}
@@ -0,0 +1,404 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use image::error::ImageError;
+
+use crate::{
+    hitable::shapes::{MeshData, TriangleMesh},
+    loaders::mtl::load_mtl,
+    materials::Lambertian,
+    texture::Solid,
+    types::Vec3,
+    Material,
+};
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    Texture {
+        path: PathBuf,
+        error: ImageError,
+    },
+    UnknownMaterial {
+        path: PathBuf,
+        line: usize,
+        name: String,
+    },
+    NoFaces(PathBuf),
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ObjError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            ObjError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+            ObjError::Texture { path, error } => {
+                write!(f, "error in loading texture {}: {}", path.display(), error)
+            }
+            ObjError::UnknownMaterial { path, line, name } => write!(
+                f,
+                "{}:{}: material {} is not defined in any mtllib",
+                path.display(),
+                line,
+                name
+            ),
+            ObjError::NoFaces(path) => write!(f, "{}: file has no faces", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+/// Indices of the position, texture coordinate and normal of a face corner
+type Corner = (usize, Option<usize>, Option<usize>);
+
+/// Triangles that share a material
+type Group = (Arc<dyn Material>, Vec<[Corner; 3]>);
+
+/// Loads a Wavefront .obj file along with the .mtl files it references.
+///
+/// Faces are grouped by the material they use and every group becomes one
+/// `TriangleMesh`. Polygons are split into triangle fans. A group only keeps
+/// normals or texture coordinates if every one of its corners has them.
+/// Faces before any `usemtl` get a grey `Lambertian`.
+pub fn load_obj(path: impl AsRef<Path>) -> Result<Vec<TriangleMesh<Arc<dyn Material>>>, ObjError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path).map_err(|error| ObjError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+
+    let mut materials: HashMap<String, Arc<dyn Material>> = HashMap::new();
+    let mut groups: Vec<Group> = Vec::new();
+    let mut group_index: HashMap<Option<String>, usize> = HashMap::new();
+    let mut current_material: Option<String> = None;
+
+    for (i, line) in source.lines().enumerate() {
+        let error = |message: String| ObjError::Parse {
+            path: path.to_path_buf(),
+            line: i + 1,
+            message,
+        };
+
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(v) => v,
+            None => continue,
+        };
+
+        match keyword {
+            "v" => positions.push(parse_vec3(tokens).map_err(error)?),
+            "vn" => normals.push(parse_vec3(tokens).map_err(error)?),
+            "vt" => {
+                let u = parse_f64(tokens.next()).map_err(error)?;
+                // v is optional for 1D textures
+                let v = match tokens.next() {
+                    Some(v) => parse_f64(Some(v)).map_err(error)?,
+                    None => 0.0,
+                };
+                uvs.push((u, v));
+            }
+            "f" => {
+                let corners = tokens
+                    .map(|token| parse_corner(token, positions.len(), uvs.len(), normals.len()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+
+                if corners.len() < 3 {
+                    return Err(error(format!(
+                        "face needs at least 3 vertices, found {}",
+                        corners.len()
+                    )));
+                }
+
+                let group = match group_index.get(&current_material) {
+                    Some(&v) => v,
+                    None => {
+                        let material: Arc<dyn Material> = match &current_material {
+                            Some(name) => match materials.get(name) {
+                                Some(v) => v.clone(),
+                                None => {
+                                    return Err(ObjError::UnknownMaterial {
+                                        path: path.to_path_buf(),
+                                        line: i + 1,
+                                        name: name.clone(),
+                                    })
+                                }
+                            },
+                            None => Arc::new(Lambertian::new(Solid::new(Vec3::splat(0.5)))),
+                        };
+                        groups.push((material, Vec::new()));
+                        group_index.insert(current_material.clone(), groups.len() - 1);
+                        groups.len() - 1
+                    }
+                };
+
+                let faces = &mut groups[group].1;
+                for j in 1..corners.len() - 1 {
+                    faces.push([corners[0], corners[j], corners[j + 1]]);
+                }
+            }
+            "mtllib" => {
+                // One or more files, later ones override materials of the same name
+                let mut files = tokens.peekable();
+                if files.peek().is_none() {
+                    return Err(error("mtllib without a file name".to_string()));
+                }
+                for file in files {
+                    materials.extend(load_mtl(&directory.join(file))?);
+                }
+            }
+            "usemtl" => {
+                let name = parse_name(line, keyword)
+                    .ok_or_else(|| error("usemtl without a material name".to_string()))?;
+                current_material = Some(name.to_string());
+            }
+            // Objects, groups, smoothing groups and everything else are ignored
+            _ => (),
+        }
+    }
+
+    if groups.is_empty() {
+        return Err(ObjError::NoFaces(path.to_path_buf()));
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|(material, faces)| {
+            TriangleMesh::new(build_mesh(&faces, &positions, &normals, &uvs), material)
+        })
+        .collect())
+}
+
+/// Gathers the vertices used by `faces` into their own buffers, one vertex
+/// for every distinct combination of position, uv and normal
+fn build_mesh(
+    faces: &[[Corner; 3]],
+    positions: &[Vec3],
+    normals: &[Vec3],
+    uvs: &[(f64, f64)],
+) -> MeshData {
+    let corners = || faces.iter().flatten();
+    let has_normals = corners().all(|corner| corner.2.is_some());
+    let has_uvs = corners().all(|corner| corner.1.is_some());
+
+    let mut mesh_positions = Vec::new();
+    let mut mesh_normals = Vec::new();
+    let mut mesh_uvs = Vec::new();
+    let mut vertices: HashMap<Corner, u32> = HashMap::new();
+
+    let indices = faces
+        .iter()
+        .map(|face| {
+            face.map(|(p, uv, n)| {
+                let uv = uv.filter(|_| has_uvs);
+                let n = n.filter(|_| has_normals);
+
+                *vertices.entry((p, uv, n)).or_insert_with(|| {
+                    mesh_positions.push(positions[p]);
+                    if let Some(n) = n {
+                        mesh_normals.push(normals[n].unit_vector());
+                    }
+                    if let Some(uv) = uv {
+                        mesh_uvs.push(uvs[uv]);
+                    }
+                    mesh_positions.len() as u32 - 1
+                })
+            })
+        })
+        .collect();
+
+    let mut mesh = MeshData::new(mesh_positions, indices);
+    if has_normals {
+        mesh = mesh.with_normals(mesh_normals);
+    }
+    if has_uvs {
+        mesh = mesh.with_uvs(mesh_uvs);
+    }
+
+    mesh
+}
+
+/// Parses one `v`, `v/vt`, `v//vn` or `v/vt/vn` face corner
+fn parse_corner(
+    token: &str,
+    positions: usize,
+    uvs: usize,
+    normals: usize,
+) -> Result<Corner, String> {
+    let mut parts = token.split('/');
+
+    let position = resolve_index(parts.next(), positions, "vertex")?
+        .ok_or_else(|| format!("face corner {} has no vertex index", token))?;
+    let uv = resolve_index(parts.next(), uvs, "texture coordinate")?;
+    let normal = resolve_index(parts.next(), normals, "normal")?;
+
+    if parts.next().is_some() {
+        return Err(format!("face corner {} has too many indices", token));
+    }
+
+    Ok((position, uv, normal))
+}
+
+/// Converts a 1 based index, or a negative one counting back from the
+/// latest element, into an index into a buffer of `len` elements
+fn resolve_index(token: Option<&str>, len: usize, kind: &str) -> Result<Option<usize>, String> {
+    let token = match token {
+        Some(v) if !v.is_empty() => v,
+        _ => return Ok(None),
+    };
+
+    let index: i64 = token
+        .parse()
+        .map_err(|_| format!("invalid {} index {}", kind, token))?;
+
+    let resolved = match index {
+        0 => None,
+        i if i > 0 => Some(i as usize - 1),
+        i => (len as i64)
+            .checked_add(i)
+            .filter(|&v| v >= 0)
+            .map(|v| v as usize),
+    };
+
+    match resolved {
+        Some(v) if v < len => Ok(Some(v)),
+        _ => Err(format!(
+            "{} index {} out of range, {} defined so far",
+            kind, token, len
+        )),
+    }
+}
+
+/// Material names are the rest of the line after `keyword`, so `newmtl` and
+/// `usemtl` agree on names with spaces in them
+pub(super) fn parse_name<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    Some(line[keyword.len()..].trim()).filter(|name| !name.is_empty())
+}
+
+pub(super) fn parse_f64(token: Option<&str>) -> Result<f64, String> {
+    let token = token.ok_or_else(|| "expected a number".to_string())?;
+    token
+        .parse()
+        .map_err(|_| format!("invalid number {}", token))
+}
+
+pub(super) fn parse_vec3<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Result<Vec3, String> {
+    let x = parse_f64(tokens.next())?;
+    let y = parse_f64(tokens.next())?;
+    let z = parse_f64(tokens.next())?;
+
+    Ok(Vec3::new(x, y, z))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, process};
+
+    use super::{load_obj, ObjError};
+
+    const TRIANGLE: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+
+    /// Writes `files` to a directory of their own and returns the path of the first
+    fn write_files(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = env::temp_dir().join(format!("obj_{}_{}", test, process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        for (name, source) in files {
+            fs::write(directory.join(name), source).unwrap();
+        }
+        directory.join(files[0].0)
+    }
+
+    #[test]
+    fn materials_from_several_libraries() {
+        let obj = format!("mtllib a.mtl b.mtl\nusemtl dark red\n{}f 1 2 3\n", TRIANGLE);
+        let path = write_files(
+            "libraries",
+            &[
+                ("model.obj", &obj),
+                ("a.mtl", "newmtl white\nKd 1 1 1\n"),
+                ("b.mtl", "newmtl dark red\nKd 0.5 0 0\n"),
+            ],
+        );
+
+        assert_eq!(load_obj(path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn index_out_of_range() {
+        let obj = format!("{}f 1 2 4\n", TRIANGLE);
+        let path = write_files("index", &[("model.obj", &obj)]);
+
+        match load_obj(path).err() {
+            Some(ObjError::Parse { line, message, .. }) => {
+                assert_eq!(line, 4);
+                assert!(message.contains("out of range"), "{}", message);
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn missing_mtllib() {
+        let obj = format!("mtllib missing.mtl\n{}f 1 2 3\n", TRIANGLE);
+        let path = write_files("mtllib", &[("model.obj", &obj)]);
+
+        match load_obj(path).err() {
+            Some(ObjError::Io { path, .. }) => assert!(path.ends_with("missing.mtl")),
+            other => panic!("expected an io error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_material() {
+        let obj = format!("mtllib a.mtl\nusemtl blue\n{}f 1 2 3\n", TRIANGLE);
+        let path = write_files(
+            "material",
+            &[("model.obj", &obj), ("a.mtl", "newmtl red\nKd 1 0 0\n")],
+        );
+
+        match load_obj(path).err() {
+            Some(ObjError::UnknownMaterial { line, name, .. }) => {
+                assert_eq!(line, 6);
+                assert_eq!(name, "blue");
+            }
+            other => panic!("expected an unknown material error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bad_number() {
+        let path = write_files("number", &[("model.obj", "v 0 0 0\nv 1 zero 0\n")]);
+
+        match load_obj(path).err() {
+            Some(ObjError::Parse { line, message, .. }) => {
+                assert_eq!(line, 2);
+                assert_eq!(message, "invalid number zero");
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,415 @@
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    hitable::shapes::{MeshData, TriangleMesh},
+    types::Vec3,
+    Material,
+};
+
+#[derive(Debug)]
+pub enum PlyError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Header {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    Data {
+        path: PathBuf,
+        element: String,
+        row: usize,
+        message: String,
+    },
+    NoFaces(PathBuf),
+}
+
+impl Display for PlyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            PlyError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            PlyError::Header {
+                path,
+                line,
+                message,
+            } => write!(f, "{}: header line {}: {}", path.display(), line, message),
+            PlyError::Data {
+                path,
+                element,
+                row,
+                message,
+            } => write!(f, "{}: {} {}: {}", path.display(), element, row, message),
+            PlyError::NoFaces(path) => write!(f, "{}: file has no faces", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for PlyError {}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+}
+
+enum PropertyKind {
+    Scalar(Scalar),
+    /// Type of the item count followed by the type of the items
+    List(Scalar, Scalar),
+}
+
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Reads the values of the body one at a time, whatever the format
+enum Body<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { data: &'a [u8], big_endian: bool },
+}
+
+impl<'a> Body<'a> {
+    fn read(&mut self, scalar: Scalar) -> Result<f64, String> {
+        match self {
+            Body::Ascii(tokens) => {
+                let token = tokens.next().ok_or("unexpected end of file")?;
+                token
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid number {}", token))
+            }
+            Body::Binary { data, big_endian } => {
+                if data.len() < scalar.size() {
+                    return Err("unexpected end of file".to_string());
+                }
+                let (bytes, rest) = data.split_at(scalar.size());
+                *data = rest;
+
+                let mut buf = [0; 8];
+                buf[..bytes.len()].copy_from_slice(bytes);
+                if *big_endian {
+                    buf[..bytes.len()].reverse();
+                }
+
+                Ok(match scalar {
+                    Scalar::I8 => i8::from_le_bytes([buf[0]]) as f64,
+                    Scalar::U8 => buf[0] as f64,
+                    Scalar::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    Scalar::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    Scalar::I32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    Scalar::U32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    Scalar::F32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    Scalar::F64 => f64::from_le_bytes(buf),
+                })
+            }
+        }
+    }
+}
+
+/// Loads a Stanford .ply mesh in the ascii or either of the binary formats.
+///
+/// Reads vertex positions along with normals (`nx`, `ny`, `nz`), texture
+/// coordinates (`u`/`v`, `s`/`t` or `texture_u`/`texture_v`) and colors
+/// (`red`, `green`, `blue`) when every vertex has them. Integer colors are
+/// scaled down from 0..255. Use a `VertexColor` texture in `material` to
+/// render with the vertex colors. Polygons are split into triangle fans and
+/// elements other than `vertex` and `face` are skipped.
+pub fn load_ply<T: Material>(
+    path: impl AsRef<Path>,
+    material: T,
+) -> Result<TriangleMesh<T>, PlyError> {
+    let path = path.as_ref();
+    let source = fs::read(path).map_err(|error| PlyError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+
+    let (format, elements, body) = parse_header(path, &source)?;
+    let mut body = match format {
+        Format::Ascii => Body::Ascii(
+            std::str::from_utf8(body)
+                .map_err(|_| PlyError::Data {
+                    path: path.to_path_buf(),
+                    element: "body".to_string(),
+                    row: 0,
+                    message: "ascii body is not valid utf-8".to_string(),
+                })?
+                .split_ascii_whitespace(),
+        ),
+        Format::BinaryLittleEndian | Format::BinaryBigEndian => Body::Binary {
+            data: body,
+            big_endian: format == Format::BinaryBigEndian,
+        },
+    };
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut colors = Vec::new();
+    let mut indices = Vec::new();
+
+    for element in &elements {
+        let find = |names: &[&str]| {
+            element
+                .properties
+                .iter()
+                .position(|property| names.contains(&property.name.as_str()))
+        };
+        let position = [find(&["x"]), find(&["y"]), find(&["z"])];
+        let normal = [find(&["nx"]), find(&["ny"]), find(&["nz"])];
+        let uv = [
+            find(&["u", "s", "texture_u", "texture_s"]),
+            find(&["v", "t", "texture_v", "texture_t"]),
+        ];
+        let color = [find(&["red"]), find(&["green"]), find(&["blue"])];
+        let indices_property = find(&["vertex_indices", "vertex_index"]);
+
+        let mut values = vec![0.0; element.properties.len()];
+        let mut list = Vec::new();
+
+        for row in 0..element.count {
+            let error = |message: String| PlyError::Data {
+                path: path.to_path_buf(),
+                element: element.name.clone(),
+                row,
+                message,
+            };
+
+            for (i, property) in element.properties.iter().enumerate() {
+                match property.kind {
+                    PropertyKind::Scalar(scalar) => values[i] = body.read(scalar).map_err(error)?,
+                    PropertyKind::List(count, item) => {
+                        let count = body.read(count).map_err(error)? as usize;
+                        let items = (0..count)
+                            .map(|_| body.read(item))
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err(error)?;
+
+                        // Only the face indices are kept, other lists are just skipped over
+                        if Some(i) == indices_property {
+                            list = items;
+                        }
+                    }
+                }
+            }
+
+            match element.name.as_str() {
+                "vertex" => {
+                    let get = |property: Option<usize>| property.map(|i| values[i]);
+
+                    match position {
+                        [Some(x), Some(y), Some(z)] => {
+                            positions.push(Vec3::new(values[x], values[y], values[z]))
+                        }
+                        _ => return Err(error("vertex is missing x, y or z".to_string())),
+                    }
+                    if let [Some(x), Some(y), Some(z)] = normal.map(get) {
+                        normals.push(Vec3::new(x, y, z).unit_vector());
+                    }
+                    if let [Some(u), Some(v)] = uv.map(get) {
+                        uvs.push((u, v));
+                    }
+                    if let [Some(r), Some(g), Some(b)] = color {
+                        let scale = color_scale(&element.properties[r].kind);
+                        colors.push(Vec3::new(values[r], values[g], values[b]) * scale);
+                    }
+                }
+                "face" => {
+                    if indices_property.is_none() {
+                        return Err(error("face has no vertex_indices".to_string()));
+                    }
+                    if list.len() < 3 {
+                        return Err(error(format!(
+                            "face needs at least 3 vertices, found {}",
+                            list.len()
+                        )));
+                    }
+                    if let Some(&index) = list
+                        .iter()
+                        .find(|&&v| v < 0.0 || v as usize >= positions.len())
+                    {
+                        return Err(error(format!(
+                            "vertex index {} out of range, {} vertices",
+                            index,
+                            positions.len()
+                        )));
+                    }
+
+                    for j in 1..list.len() - 1 {
+                        indices.push([list[0] as u32, list[j] as u32, list[j + 1] as u32]);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    if indices.is_empty() {
+        return Err(PlyError::NoFaces(path.to_path_buf()));
+    }
+
+    let mut mesh = MeshData::new(positions, indices);
+    if normals.len() == mesh.positions.len() {
+        mesh = mesh.with_normals(normals);
+    }
+    if uvs.len() == mesh.positions.len() {
+        mesh = mesh.with_uvs(uvs);
+    }
+    if colors.len() == mesh.positions.len() {
+        mesh = mesh.with_colors(colors);
+    }
+
+    Ok(TriangleMesh::new(mesh, material))
+}
+
+/// Splits the file into the format, the elements declared in the header
+/// and the bytes of the body that follows it
+fn parse_header<'a>(
+    path: &Path,
+    source: &'a [u8],
+) -> Result<(Format, Vec<Element>, &'a [u8]), PlyError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut rest = source;
+    let mut line_number = 0;
+
+    loop {
+        line_number += 1;
+        let error = |message: &str| PlyError::Header {
+            path: path.to_path_buf(),
+            line: line_number,
+            message: message.to_string(),
+        };
+
+        let end = rest
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| error("header has no end_header"))?;
+        let line = std::str::from_utf8(&rest[..end])
+            .map_err(|_| error("header is not valid utf-8"))?
+            .trim();
+        rest = &rest[end + 1..];
+
+        if line_number == 1 {
+            if line != "ply" {
+                return Err(error("not a ply file"));
+            }
+            continue;
+        }
+
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(error(&format!("unknown format {}", name))),
+                });
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| error(&format!("invalid element count {}", count)))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => {
+                let scalar = |name: &str| {
+                    Scalar::parse(name).ok_or_else(|| error(&format!("unknown type {}", name)))
+                };
+                let kind = PropertyKind::List(scalar(count)?, scalar(item)?);
+                elements
+                    .last_mut()
+                    .ok_or_else(|| error("property before any element"))?
+                    .properties
+                    .push(Property {
+                        name: name.to_string(),
+                        kind,
+                    });
+            }
+            ["property", ty, name] => {
+                let scalar =
+                    Scalar::parse(ty).ok_or_else(|| error(&format!("unknown type {}", ty)))?;
+                elements
+                    .last_mut()
+                    .ok_or_else(|| error("property before any element"))?
+                    .properties
+                    .push(Property {
+                        name: name.to_string(),
+                        kind: PropertyKind::Scalar(scalar),
+                    });
+            }
+            ["end_header"] => break,
+            ["comment", ..] | ["obj_info", ..] | [] => (),
+            _ => return Err(error(&format!("unexpected line {}", line))),
+        }
+    }
+
+    match format {
+        Some(format) => Ok((format, elements, rest)),
+        None => Err(PlyError::Header {
+            path: path.to_path_buf(),
+            line: line_number,
+            message: "header has no format".to_string(),
+        }),
+    }
+}
+
+/// Integer colors go from 0 to 255, floating point ones from 0 to 1
+fn color_scale(kind: &PropertyKind) -> f64 {
+    match kind {
+        PropertyKind::Scalar(Scalar::F32 | Scalar::F64) => 1.0,
+        _ => 1.0 / 255.0,
+    }
+}
@@ -4,6 +4,7 @@ mod aabb;
 mod camera;
 mod demos;
 mod hitable;
+mod loaders;
 mod materials;
 mod texture;
 mod types;
@@ -23,15 +24,40 @@ pub trait Asf64: num_traits::AsPrimitive<f64> {}
 impl<T: num_traits::AsPrimitive<f64>> Asf64 for T {}
 
 const NUM_SAMPLES: u16 = 500;
+const BENCH_SAMPLES: u16 = 4;
 const VERTICAL_PARTITION: usize = 30;
 const HORIZONTAL_PARTITION: usize = 30;
 const WIDTH: usize = 800;
 const HEIGHT: usize = 800;
 
 fn main() -> Result<(), String> {
+    if std::env::args().any(|arg| arg == "--bench") {
+        bench(WIDTH, HEIGHT);
+        return Ok(());
+    }
+
     run(WIDTH, HEIGHT)
 }
 
+fn bench(width: usize, height: usize) {
+    let demos: [DemoWrapper; 10] = [
+        DemoWrapper::BVHNode(Box::new(demos::CheckeredMotionBlur {})),
+        DemoWrapper::BVHNode(Box::new(demos::TwoSpheres {})),
+        DemoWrapper::BVHNode(Box::new(demos::PerlinNoiseBall {})),
+        DemoWrapper::BVHNode(Box::new(demos::ImageTextureDemo {})),
+        DemoWrapper::BVHNode(Box::new(demos::SimpleLight {})),
+        DemoWrapper::BVHNode(Box::new(demos::Instances {})),
+        DemoWrapper::BVHNode(Box::new(demos::CornellSmokeAndFog {})),
+        DemoWrapper::Scene(Box::new(demos::Shapes {})),
+        DemoWrapper::BVHNode(Box::new(demos::GltfSceneDemo {})),
+        DemoWrapper::BVHNode(Box::new(demos::Terrain {})),
+    ];
+
+    for demo in demos.iter() {
+        demo.bench_accelerators(width, height, BENCH_SAMPLES);
+    }
+}
+
 #[cfg(feature = "gui")]
 fn run(mut width: usize, mut height: usize) -> Result<(), String> {
     use sdl2::{
@@ -116,6 +142,18 @@ fn run(mut width: usize, mut height: usize) -> Result<(), String> {
                             active_demo = DemoWrapper::HitableList(Box::new(demos::CornellBox {}));
                             should_update = true;
                         }
+                        Some(Keycode::Num9) => {
+                            active_demo = DemoWrapper::Scene(Box::new(demos::Shapes {}));
+                            should_update = true;
+                        }
+                        Some(Keycode::Num0) => {
+                            active_demo = DemoWrapper::BVHNode(Box::new(demos::GltfSceneDemo {}));
+                            should_update = true;
+                        }
+                        Some(Keycode::Minus) => {
+                            active_demo = DemoWrapper::BVHNode(Box::new(demos::Terrain {}));
+                            should_update = true;
+                        }
                         None => unreachable!(),
                         _ => (),
                     };
@@ -155,7 +193,7 @@ fn run(mut width: usize, mut height: usize) -> Result<(), String> {
 
 #[cfg(not(feature = "gui"))]
 fn run(width: usize, height: usize) -> Result<(), String> {
-    let demos: [DemoWrapper; 8] = [
+    let demos: [DemoWrapper; 11] = [
         DemoWrapper::BVHNode(Box::new(demos::CheckeredMotionBlur {})),
         DemoWrapper::BVHNode(Box::new(demos::TwoSpheres {})),
         DemoWrapper::BVHNode(Box::new(demos::PerlinNoiseBall {})),
@@ -164,6 +202,9 @@ fn run(width: usize, height: usize) -> Result<(), String> {
         DemoWrapper::BVHNode(Box::new(demos::Instances {})),
         DemoWrapper::BVHNode(Box::new(demos::CornellSmokeAndFog {})),
         DemoWrapper::HitableList(Box::new(demos::CornellBox {})),
+        DemoWrapper::Scene(Box::new(demos::Shapes {})),
+        DemoWrapper::BVHNode(Box::new(demos::GltfSceneDemo {})),
+        DemoWrapper::BVHNode(Box::new(demos::Terrain {})),
     ];
 
     for demo in demos.iter() {
@@ -0,0 +1,92 @@
+use rand::prelude::SmallRng;
+
+use crate::{
+    hitable::HitRecord,
+    materials::perturb_normal,
+    types::{Ray, Vec3},
+    Material, Texture,
+};
+
+/// Step in texture coordinates used to measure the slope of the height texture
+const DELTA: f64 = 1e-3;
+
+/// Wraps a material and bends the normal it sees by the slope of a height
+/// texture, so the surface looks bumpy without changing its shape.
+///
+/// The height is the average of the channels. `strength` scales the slope,
+/// negative values turn bumps into dents. The slope is measured by stepping
+/// `DELTA` in u & v and as far along the surface as that goes, so both image
+/// and solid textures work. The surface needs tangents, `Cone`, `Torus`,
+/// `MovingSphere` and sphere traced SDFs have none and are left with their
+/// geometric normal
+#[derive(Clone)]
+pub struct BumpMapped<M: Material, T: Texture> {
+    material: M,
+    heights: T,
+    strength: f64,
+}
+
+impl<M: Material, T: Texture> BumpMapped<M, T> {
+    pub fn new(material: M, heights: T, strength: f64) -> Self {
+        Self {
+            material,
+            heights,
+            strength,
+        }
+    }
+
+    fn height(&self, u: f64, v: f64, p: Vec3) -> f64 {
+        let color = self.heights.value(u, v, p);
+        (color.x() + color.y() + color.z()) / 3.0
+    }
+
+    fn shade<'a>(&self, hit_rec: &HitRecord<'a>) -> HitRecord<'a> {
+        HitRecord {
+            normal: self.normal(hit_rec).unwrap_or(hit_rec.normal),
+            ..*hit_rec
+        }
+    }
+
+    fn normal(&self, hit_rec: &HitRecord) -> Option<Vec3> {
+        let tangent = hit_rec.tangent?;
+        // Without one, guess that v is scaled like u
+        let bitangent = hit_rec
+            .bitangent
+            .unwrap_or_else(|| hit_rec.normal.cross(&tangent));
+
+        let (u, v, p) = (hit_rec.u, hit_rec.v, hit_rec.p);
+        let height = self.height(u, v, p);
+        let du = (self.height(u + DELTA, v, p + tangent * DELTA) - height) / DELTA;
+        let dv = (self.height(u, v + DELTA, p + bitangent * DELTA) - height) / DELTA;
+
+        perturb_normal(
+            hit_rec,
+            Vec3::new(-du * self.strength, -dv * self.strength, 1.0),
+        )
+    }
+}
+
+impl<M: Material, T: Texture + Send + Sync> Material for BumpMapped<M, T> {
+    fn scatter(
+        &self,
+        ray: &Ray,
+        hit_rec: &HitRecord,
+        rng: &mut SmallRng,
+    ) -> (Vec3, f64, Option<Ray>) {
+        self.material.scatter(ray, &self.shade(hit_rec), rng)
+    }
+
+    fn scatter_pdf(&self, ray: &Ray, hit_rec: &HitRecord, scattered: &Ray) -> f64 {
+        self.material
+            .scatter_pdf(ray, &self.shade(hit_rec), scattered)
+    }
+
+    fn sampling_pdf(&self, ray: &Ray, hit_rec: &HitRecord, scattered: &Ray) -> Option<f64> {
+        self.material
+            .sampling_pdf(ray, &self.shade(hit_rec), scattered)
+    }
+
+    fn emit(&self, u: f64, v: f64, p: Vec3) -> Vec3 {
+        self.material.emit(u, v, p)
+    }
+}
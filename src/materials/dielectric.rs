@@ -2,7 +2,7 @@ use rand::{prelude::SmallRng, Rng};
 
 use crate::{
     hitable::HitRecord,
-    materials::{reflect, refract, schlick},
+    materials::{bent_differentials, reflect, refract, schlick},
     types::{Ray, Vec3},
     Material,
 };
@@ -40,26 +40,29 @@ impl Material for Dielectric {
 
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
 
-        if cannot_refract || schlick(cosine, refraction_ratio) > rng.gen::<f64>() {
-            let direction = reflect(unit_direction, hit_rec.normal);
-            (
-                attenuation,
-                0.0,
-                Some(Ray::new(hit_rec.p, direction, ray_in.time())),
-            )
-        } else if let Some(direction) = refract(unit_direction, hit_rec.normal, refraction_ratio) {
-            (
-                attenuation,
-                0.0,
-                Some(Ray::new(hit_rec.p, direction, ray_in.time())),
-            )
+        let refracted = if cannot_refract || schlick(cosine, refraction_ratio) > rng.gen::<f64>() {
+            None
         } else {
-            let direction = reflect(unit_direction, hit_rec.normal);
-            (
-                attenuation,
-                0.0,
-                Some(Ray::new(hit_rec.p, direction, ray_in.time())),
-            )
-        }
+            refract(unit_direction, hit_rec.normal, refraction_ratio)
+        };
+
+        let (direction, differentials) = match refracted {
+            Some(direction) => (
+                direction,
+                bent_differentials(ray_in, hit_rec, |d| {
+                    refract(d, hit_rec.normal, refraction_ratio)
+                }),
+            ),
+            None => (
+                reflect(unit_direction, hit_rec.normal),
+                bent_differentials(ray_in, hit_rec, |d| Some(reflect(d, hit_rec.normal))),
+            ),
+        };
+
+        (
+            attenuation,
+            0.0,
+            Some(Ray::new(hit_rec.p, direction, ray_in.time()).with_differentials(differentials)),
+        )
     }
 }
@@ -0,0 +1,79 @@
+use rand::{prelude::SmallRng, Rng};
+
+use crate::{
+    hitable::HitRecord,
+    materials::{random_point_in_unit_hemisphere, random_point_in_unit_sphere},
+    types::{Ray, Vec3},
+    Material, Texture,
+};
+
+/// Share of the light reflected off the surface of a fiber, the rest
+/// goes through it and comes out tinted by its color
+const SPECULAR: f64 = 0.2;
+
+/// Fibers of hair or fur, meant for `Curves`.
+///
+/// A simplified take on the Marschner model. Light bounces off the surface
+/// of the fiber into a cone around it, the same angle from the fiber as it came in,
+/// which gives hair its highlights running across the strands. The rest is scattered
+/// evenly and picks up `color`. `roughness` spreads the highlight.
+///
+/// Directions are picked in proportion to how much light goes there so the
+/// attenuation alone carries the color and `scatter_pdf` matches the pdf.
+/// Only the direction of the tangent is used, surfaces without one use a
+/// direction perpendicular to the normal
+#[derive(Clone)]
+pub struct Hair<T: Texture> {
+    color: T,
+    roughness: f64,
+}
+
+impl<T: Texture> Hair<T> {
+    pub fn new(color: T, roughness: f64) -> Self {
+        Self { color, roughness }
+    }
+}
+
+impl<T: Texture + Send + Sync> Material for Hair<T> {
+    fn scatter(
+        &self,
+        ray: &Ray,
+        hit_rec: &HitRecord,
+        rng: &mut SmallRng,
+    ) -> (Vec3, f64, Option<Ray>) {
+        if rng.gen::<f64>() >= SPECULAR {
+            let direction = random_point_in_unit_hemisphere(rng, &hit_rec.normal);
+            let scattered_ray = Ray::new(hit_rec.p, direction.unit_vector(), ray.time());
+            return (self.color.value_at(hit_rec), 1.0, Some(scattered_ray));
+        }
+
+        let tangent = hit_rec.tangent.map(|t| t.unit_vector()).unwrap_or_else(|| {
+            let other = if hit_rec.normal.x().abs() > 0.9 {
+                Vec3::new(0.0, 1.0, 0.0)
+            } else {
+                Vec3::new(1.0, 0.0, 0.0)
+            };
+            hit_rec.normal.cross(&other).unit_vector()
+        });
+
+        // Mirror the incoming angle to the fiber and pick a spot
+        // around the half of the cone on the side of the normal
+        let incoming = -ray.direction.unit_vector();
+        let along = incoming.dot(&tangent);
+        let normal = (hit_rec.normal - tangent * hit_rec.normal.dot(&tangent)).unit_vector();
+        let binormal = tangent.cross(&normal);
+        let phi = (rng.gen::<f64>() - 0.5) * std::f64::consts::PI;
+        let around =
+            (normal * phi.cos() + binormal * phi.sin()) * (1.0 - along * along).max(0.0).sqrt();
+
+        let direction =
+            around - tangent * along + random_point_in_unit_sphere(rng) * self.roughness;
+        let scattered_ray = Ray::new(hit_rec.p, direction.unit_vector(), ray.time());
+
+        (Vec3::splat(1.0), 1.0, Some(scattered_ray))
+    }
+
+    fn scatter_pdf(&self, _ray: &Ray, _hit_rec: &HitRecord, _scattered: &Ray) -> f64 {
+        1.0
+    }
+}
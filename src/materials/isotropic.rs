@@ -25,7 +25,7 @@ impl<T: Texture + Send + Sync> Material for Isotropic<T> {
         rng: &mut SmallRng,
     ) -> (Vec3, f64, Option<Ray>) {
         (
-            self.texture.value(hit_rec.u, hit_rec.v, hit_rec.p),
+            self.texture.value_at(hit_rec),
             0.0,
             Some(Ray::new(
                 hit_rec.p,
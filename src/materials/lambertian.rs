@@ -29,7 +29,7 @@ impl<T: Texture + Send + Sync> Material for Lambertian<T> {
 
         let scattered_ray = Ray::new(hit_rec.p, direction.unit_vector(), ray.time());
         (
-            self.albedo.value(hit_rec.u, hit_rec.v, hit_rec.p),
+            self.albedo.value_at(hit_rec),
             0.5 / std::f64::consts::PI,
             Some(scattered_ray),
         )
@@ -44,4 +44,13 @@ impl<T: Texture + Send + Sync> Material for Lambertian<T> {
             cosine / std::f64::consts::PI
         }
     }
+
+    /// `scatter` picks directions uniformly over the hemisphere around the normal
+    fn sampling_pdf(&self, _ray: &Ray, hit_rec: &HitRecord, scattered: &Ray) -> Option<f64> {
+        if hit_rec.normal.dot(&scattered.direction) > 0.0 {
+            Some(0.5 / std::f64::consts::PI)
+        } else {
+            Some(0.0)
+        }
+    }
 }
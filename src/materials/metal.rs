@@ -2,7 +2,7 @@ use rand::prelude::SmallRng;
 
 use crate::{
     hitable::HitRecord,
-    materials::{random_point_in_unit_sphere, reflect},
+    materials::{bent_differentials, random_point_in_unit_sphere, reflect},
     types::{Ray, Vec3},
     Material,
 };
@@ -31,11 +31,15 @@ impl Material for Metal {
         rng: &mut SmallRng,
     ) -> (Vec3, f64, Option<Ray>) {
         let reflected_ray = reflect(ray_in.direction.unit_vector(), hit_rec.normal);
+        // Differentials follow the mirror direction, fuzz is left out
         let scattered_ray = Ray::new(
             hit_rec.p,
             reflected_ray + random_point_in_unit_sphere(rng) * self.fuzz,
             ray_in.time(),
-        );
+        )
+        .with_differentials(bent_differentials(ray_in, hit_rec, |direction| {
+            Some(reflect(direction, hit_rec.normal))
+        }));
 
         if scattered_ray.direction.dot(&hit_rec.normal) > 0.0 {
             (self.albedo, 0.0, Some(scattered_ray))
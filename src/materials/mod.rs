@@ -1,19 +1,28 @@
+mod bump_mapped;
 mod dielectric;
 mod diffuse_light;
+mod hair;
 mod isotropic;
 mod lambertian;
 mod metal;
+mod normal_mapped;
 
+pub use bump_mapped::BumpMapped;
 pub use dielectric::Dielectric;
 pub use diffuse_light::DiffuseLight;
+pub use hair::Hair;
 pub use isotropic::Isotropic;
 pub use lambertian::Lambertian;
 pub use metal::Metal;
+pub use normal_mapped::NormalMapped;
+
+use std::sync::Arc;
+
 use rand::{prelude::SmallRng, Rng};
 
 use crate::{
     hitable::HitRecord,
-    types::{Ray, Vec3},
+    types::{OffsetRay, Ray, RayDifferentials, Vec3},
 };
 
 pub trait Material: Send + Sync {
@@ -32,11 +41,41 @@ pub trait Material: Send + Sync {
         0.0
     }
 
+    /// Density over solid angle of `scatter` picking the direction of `scattered`.
+    /// `None` for materials that can't tell, like mirrors and glass, whose rays
+    /// are never swapped for ones towards the lights
+    fn sampling_pdf(&self, _ray: &Ray, _hit_rec: &HitRecord, _scattered: &Ray) -> Option<f64> {
+        None
+    }
+
     fn emit(&self, _u: f64, _v: f64, _p: Vec3) -> Vec3 {
         Vec3::splat(0.0)
     }
 }
 
+impl<T: Material + ?Sized> Material for Arc<T> {
+    fn scatter(
+        &self,
+        ray: &Ray,
+        hit_rec: &HitRecord,
+        rng: &mut SmallRng,
+    ) -> (Vec3, f64, Option<Ray>) {
+        self.as_ref().scatter(ray, hit_rec, rng)
+    }
+
+    fn scatter_pdf(&self, ray: &Ray, hit_rec: &HitRecord, scattered: &Ray) -> f64 {
+        self.as_ref().scatter_pdf(ray, hit_rec, scattered)
+    }
+
+    fn sampling_pdf(&self, ray: &Ray, hit_rec: &HitRecord, scattered: &Ray) -> Option<f64> {
+        self.as_ref().sampling_pdf(ray, hit_rec, scattered)
+    }
+
+    fn emit(&self, u: f64, v: f64, p: Vec3) -> Vec3 {
+        self.as_ref().emit(u, v, p)
+    }
+}
+
 // Christophe Schlick's Polynomial approximation to figure out reflectivity as the angle changes
 // See Fresnel Equations, https://en.wikipedia.org/wiki/Fresnel_equations
 fn schlick(cosine: f64, reflection_index: f64) -> f64 {
@@ -61,6 +100,29 @@ fn refract(incident: Vec3, normal: Vec3, ni_over_nt: f64) -> Option<Vec3> {
     }
 }
 
+/// Differentials for a ray leaving a mirror or glass surface, `bend` turns
+/// an incoming direction into the outgoing one. Offset rays start where they
+/// cross the plane tangent to the surface. How the normal changes across the
+/// surface is left out, so curved mirrors spread the footprint less than they should
+fn bent_differentials(
+    ray: &Ray,
+    hit_rec: &HitRecord,
+    bend: impl Fn(Vec3) -> Option<Vec3>,
+) -> Option<RayDifferentials> {
+    let differentials = ray.differentials?;
+    let offset = |offset: OffsetRay| {
+        Some(OffsetRay {
+            origin: offset.on_plane(hit_rec.p, hit_rec.normal)?,
+            direction: bend(offset.direction.unit_vector())?,
+        })
+    };
+
+    Some(RayDifferentials {
+        x: offset(differentials.x)?,
+        y: offset(differentials.y)?,
+    })
+}
+
 fn random_point_in_unit_sphere<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
     let u: f64 = rng.gen();
     let v: f64 = rng.gen();
@@ -99,6 +161,35 @@ fn random_point_in_unit_hemisphere<R: Rng + ?Sized>(rng: &mut R, normal: &Vec3)
     }
 }
 
+/// Turns `local`, a direction in the tangent space of the hit, into the
+/// shading normal. x goes along the tangent, y along the bitangent and z
+/// along the normal of the side the tangents wind around, so a bump facing
+/// out of the surface does so from either side. The result is on the same
+/// side as `hit_rec.normal`. `None` if the surface has no tangent
+fn perturb_normal(hit_rec: &HitRecord, local: Vec3) -> Option<Vec3> {
+    let normal = hit_rec.normal;
+    let tangent = hit_rec.tangent?;
+    let bitangent = hit_rec.bitangent.unwrap_or_else(|| normal.cross(&tangent));
+
+    // Straighten out the frame, interpolated normals and sheared
+    // texture coordinates leave it a little skewed
+    let tangent = tangent - normal * normal.dot(&tangent);
+    if tangent.sq_len() < 1e-12 {
+        return None;
+    }
+    let tangent = tangent.unit_vector();
+    let bitangent = bitangent - normal * normal.dot(&bitangent) - tangent * tangent.dot(&bitangent);
+    if bitangent.sq_len() < 1e-12 {
+        return None;
+    }
+    let bitangent = bitangent.unit_vector();
+
+    // -1 when looking at the back of the texture
+    let side = normal.dot(&tangent.cross(&bitangent)).signum();
+
+    Some(((tangent * local.x() + bitangent * local.y()) * side + normal * local.z()).unit_vector())
+}
+
 pub trait MaterialBuilder<T> {
     type Finished;
 
@@ -0,0 +1,71 @@
+use rand::prelude::SmallRng;
+
+use crate::{
+    hitable::HitRecord,
+    materials::perturb_normal,
+    types::{Ray, Vec3},
+    Material, Texture,
+};
+
+/// Wraps a material and bends the normal it sees with a normal map.
+///
+/// The texture holds tangent space normals the usual way, each channel
+/// mapped from [-1, 1] to [0, 1] so flat is (0.5, 0.5, 1). `strength` scales
+/// how far the normals lean, 1 leaves the map as it is. The surface needs
+/// tangents, `Cone`, `Torus`, `MovingSphere` and sphere traced SDFs have none
+/// and are left with their geometric normal
+#[derive(Clone)]
+pub struct NormalMapped<M: Material, T: Texture> {
+    material: M,
+    normals: T,
+    strength: f64,
+}
+
+impl<M: Material, T: Texture> NormalMapped<M, T> {
+    pub fn new(material: M, normals: T, strength: f64) -> Self {
+        Self {
+            material,
+            normals,
+            strength,
+        }
+    }
+
+    fn shade<'a>(&self, hit_rec: &HitRecord<'a>) -> HitRecord<'a> {
+        let color = self.normals.value_at(hit_rec) * 2.0 - Vec3::splat(1.0);
+        let local = Vec3::new(
+            color.x() * self.strength,
+            color.y() * self.strength,
+            color.z(),
+        );
+
+        HitRecord {
+            normal: perturb_normal(hit_rec, local).unwrap_or(hit_rec.normal),
+            ..*hit_rec
+        }
+    }
+}
+
+impl<M: Material, T: Texture + Send + Sync> Material for NormalMapped<M, T> {
+    fn scatter(
+        &self,
+        ray: &Ray,
+        hit_rec: &HitRecord,
+        rng: &mut SmallRng,
+    ) -> (Vec3, f64, Option<Ray>) {
+        self.material.scatter(ray, &self.shade(hit_rec), rng)
+    }
+
+    fn scatter_pdf(&self, ray: &Ray, hit_rec: &HitRecord, scattered: &Ray) -> f64 {
+        self.material
+            .scatter_pdf(ray, &self.shade(hit_rec), scattered)
+    }
+
+    fn sampling_pdf(&self, ray: &Ray, hit_rec: &HitRecord, scattered: &Ray) -> Option<f64> {
+        self.material
+            .sampling_pdf(ray, &self.shade(hit_rec), scattered)
+    }
+
+    fn emit(&self, u: f64, v: f64, p: Vec3) -> Vec3 {
+        self.material.emit(u, v, p)
+    }
+}
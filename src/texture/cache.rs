@@ -0,0 +1,55 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, OnceLock, Weak},
+};
+
+use image::error::ImageError;
+
+use crate::texture::{image_texture::MipMap, ColorSpace, ImageTexture};
+
+type Textures = HashMap<(PathBuf, ColorSpace), Weak<MipMap>>;
+
+// Only weak references are kept so the texels are freed along with the last
+// scene using them, instead of living until the program exits
+static TEXTURES: OnceLock<Mutex<Textures>> = OnceLock::new();
+
+/// Image textures loaded from files, shared between everything that uses the
+/// same file so it's decoded and kept in memory once
+pub struct TextureCache;
+
+impl TextureCache {
+    /// The texture at `path`, decoded if nothing is using it yet. Each call
+    /// gets its own copy to set the wrap modes and filter on, the texels and
+    /// mip pyramid are shared
+    pub fn load(
+        path: impl AsRef<Path>,
+        color_space: ColorSpace,
+    ) -> Result<ImageTexture, ImageError> {
+        // The same file reached through different relative paths or links is
+        // still one entry
+        let key = (path.as_ref().canonicalize()?, color_space);
+        if let Some(mip_map) = Self::textures().get(&key).and_then(Weak::upgrade) {
+            return Ok(ImageTexture::from_mip_map(mip_map));
+        }
+
+        // Decoding can take a while, other threads can use the cache meanwhile
+        let texture = ImageTexture::from_filename(&key.0.to_string_lossy(), color_space)?;
+
+        let mut textures = Self::textures();
+        // Keep the first copy if another thread decoded the same file meanwhile
+        if let Some(mip_map) = textures.get(&key).and_then(Weak::upgrade) {
+            return Ok(ImageTexture::from_mip_map(mip_map));
+        }
+        textures.retain(|_, v| v.strong_count() > 0);
+        textures.insert(key, Arc::downgrade(texture.mip_map()));
+        Ok(texture)
+    }
+
+    fn textures() -> MutexGuard<'static, Textures> {
+        TEXTURES
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap()
+    }
+}
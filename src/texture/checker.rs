@@ -1,4 +1,4 @@
-use crate::{types::Vec3, Texture};
+use crate::{hitable::HitRecord, types::Vec3, Texture};
 
 #[derive(Clone)]
 pub struct Checker<T: Texture + Clone> {
@@ -10,16 +10,24 @@ impl<T: Texture + Clone> Checker<T> {
     pub fn new(even: T, odd: T) -> Self {
         Self { odd, even }
     }
-}
 
-impl<T: Texture + Clone> Texture for Checker<T> {
-    fn value(&self, u: f64, v: f64, p: Vec3) -> Vec3 {
+    fn pick(&self, p: Vec3) -> &T {
         let sine_wave = f64::sin(10.0 * p.x()) * f64::sin(10.0 * p.y()) * f64::sin(10.0 * p.z());
 
         if sine_wave < 0.0 {
-            self.odd.value(u, v, p)
+            &self.odd
         } else {
-            self.even.value(u, v, p)
+            &self.even
         }
     }
 }
+
+impl<T: Texture + Clone> Texture for Checker<T> {
+    fn value(&self, u: f64, v: f64, p: Vec3) -> Vec3 {
+        self.pick(p).value(u, v, p)
+    }
+
+    fn value_at(&self, hit_rec: &HitRecord) -> Vec3 {
+        self.pick(hit_rec.p).value_at(hit_rec)
+    }
+}
@@ -1,56 +1,442 @@
-use image::{error::ImageError, io::Reader as ImageReader};
+use std::{
+    fs, mem,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+};
 
-use crate::{types::Vec3, Texture};
+use image::{codecs::hdr::HdrDecoder, error::ImageError, DynamicImage, ImageFormat};
 
+use crate::{hitable::HitRecord, types::Vec3, Texture};
+
+/// Longest a filter footprint can be compared to its width. Longer ones are
+/// widened, which blurs a little but keeps the number of texels read in check
+const MAX_ANISOTROPY: f64 = 8.0;
+
+/// Falloff of the gaussian used to weigh texels in `Filter::Ewa`
+const EWA_ALPHA: f64 = 2.0;
+
+/// Bytes taken up by the texels of every image texture alive, mip levels included
+static MEMORY_IN_USE: AtomicUsize = AtomicUsize::new(0);
+
+/// How texture coordinates outside of [0, 1] are brought back onto the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WrapMode {
+    /// Tiles the image
+    Repeat,
+    /// Stretches the texels at the edges
+    Clamp,
+    /// Tiles the image, flipping every other copy so the edges line up
+    Mirror,
+}
+
+/// How the values stored in an 8 or 16 bit image relate to light. Float
+/// images, like Radiance .hdr and OpenEXR files (with the `exr` feature),
+/// always hold linear values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// Gamma encoded color, like photos and painted albedo maps
+    Srgb,
+    /// Data used as is, like normal, roughness or height maps
+    Linear,
+}
+
+/// How texels are combined into a color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    /// The texel under the point, blocky up close and noisy far away
+    Nearest,
+    /// Blend of the four texels around the point
+    Bilinear,
+    /// Bilinear lookups in the two mip levels closest to the size of the
+    /// footprint, blended together
+    Trilinear,
+    /// Elliptically weighted average over the footprint, stays sharp along
+    /// surfaces seen at a grazing angle where trilinear blurs
+    Ewa,
+}
+
+/// How much the texture coordinates change between neighbouring pixels,
+/// along the x and y axes of the image being rendered
+#[derive(Debug, Clone, Copy)]
+pub struct Footprint {
+    pub dudx: f64,
+    pub dvdx: f64,
+    pub dudy: f64,
+    pub dvdy: f64,
+}
+
+/// Cloning is cheap, the texels are shared and only the wrap modes and the
+/// filter belong to each copy
 #[derive(Clone)]
 pub struct ImageTexture {
-    image: Vec<u8>,
-    // (width, height)
-    dimensions: (u32, u32),
-    bytes_per_scanline: u32,
-    bytes_per_pixel: u32,
+    mip_map: Arc<MipMap>,
+    wrap: (WrapMode, WrapMode),
+    filter: Filter,
 }
 
-impl ImageTexture {
-    #[allow(dead_code)]
-    pub fn from_filename(filename: &str) -> Result<Self, ImageError> {
-        let img = ImageReader::open(filename)?.decode()?;
-        let img = img.to_rgb8();
+/// Texels of a decoded image along with its mip pyramid
+pub(super) struct MipMap {
+    full: Level,
+    // Each level half the size of the one before down to 1x1. Built the
+    // first time a filter needs them and shared from then on
+    smaller: OnceLock<Vec<Level>>,
+}
 
-        let (width, _) = img.dimensions();
+struct Level {
+    // Tightly packed rows of linear RGB, top row first
+    texels: Vec<f32>,
+    width: u32,
+    height: u32,
+}
 
-        let bytes_per_pixel = 3;
+impl Level {
+    fn new(texels: Vec<f32>, width: u32, height: u32) -> Self {
+        MEMORY_IN_USE.fetch_add(texels.len() * mem::size_of::<f32>(), Ordering::Relaxed);
+        Self {
+            texels,
+            width,
+            height,
+        }
+    }
+}
 
-        Ok(Self {
-            image: img.to_vec(),
-            dimensions: img.dimensions(),
-            bytes_per_scanline: bytes_per_pixel * width,
-            bytes_per_pixel,
-        })
+impl Drop for Level {
+    fn drop(&mut self) {
+        MEMORY_IN_USE.fetch_sub(self.texels.len() * mem::size_of::<f32>(), Ordering::Relaxed);
     }
 }
 
-impl Texture for ImageTexture {
-    fn value(&self, u: f64, v: f64, _p: Vec3) -> Vec3 {
-        let (width, height) = self.dimensions;
+impl MipMap {
+    fn level(&self, level: usize) -> &Level {
+        match level {
+            0 => &self.full,
+            _ => &self.smaller.get().expect("mip pyramid was never built")[level - 1],
+        }
+    }
+
+    /// Number of levels built so far, the full image included
+    fn len(&self) -> usize {
+        1 + self.smaller.get().map_or(0, Vec::len)
+    }
+
+    /// Each level averages 2x2 blocks of the one before it
+    fn build_pyramid(&self) {
+        self.smaller.get_or_init(|| {
+            let mut levels: Vec<Level> = Vec::new();
+            loop {
+                let last = levels.last().unwrap_or(&self.full);
+                if last.width == 1 && last.height == 1 {
+                    break;
+                }
+
+                let width = (last.width / 2).max(1);
+                let height = (last.height / 2).max(1);
+
+                let mut texels = Vec::with_capacity((width * height * 3) as usize);
+                for y in 0..height {
+                    for x in 0..width {
+                        let mut sum = [0.0; 3];
+                        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                            let sx = (2 * x + dx).min(last.width - 1);
+                            let sy = (2 * y + dy).min(last.height - 1);
+                            let offset = ((sy * last.width + sx) * 3) as usize;
+                            for (s, &c) in sum.iter_mut().zip(&last.texels[offset..offset + 3]) {
+                                *s += c;
+                            }
+                        }
+                        texels.extend(sum.iter().map(|&s| s / 4.0));
+                    }
+                }
+
+                levels.push(Level::new(texels, width, height));
+            }
+            levels
+        });
+    }
+}
+
+impl ImageTexture {
+    pub fn from_filename(filename: &str, color_space: ColorSpace) -> Result<Self, ImageError> {
+        Self::from_bytes(&fs::read(filename)?, color_space)
+    }
+
+    /// Decodes an image already read into memory, like one embedded in a scene file
+    pub fn from_bytes(bytes: &[u8], color_space: ColorSpace) -> Result<Self, ImageError> {
+        // The generic decoder tone maps .hdr files down to 8 bits, so read
+        // the floats directly
+        if image::guess_format(bytes)? == ImageFormat::Hdr {
+            let decoder = HdrDecoder::new(bytes)?;
+            let metadata = decoder.metadata();
+            let texels = decoder
+                .read_image_hdr()?
+                .into_iter()
+                .flat_map(|pixel| pixel.0)
+                .collect();
+            return Ok(Self::from_texels(texels, metadata.width, metadata.height));
+        }
+
+        let img = image::load_from_memory(bytes)?;
+        Ok(Self::from_image(img, color_space))
+    }
+
+    fn from_image(img: DynamicImage, color_space: ColorSpace) -> Self {
+        let is_float = matches!(
+            img,
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+        );
+        let img = img.into_rgb32f();
+        let (width, height) = img.dimensions();
+
+        let mut texels = img.into_raw();
+        if color_space == ColorSpace::Srgb && !is_float {
+            texels.iter_mut().for_each(|c| *c = srgb_to_linear(*c));
+        }
+        Self::from_texels(texels, width, height)
+    }
+
+    fn from_texels(texels: Vec<f32>, width: u32, height: u32) -> Self {
+        Self::from_mip_map(Arc::new(MipMap {
+            full: Level::new(texels, width, height),
+            smaller: OnceLock::new(),
+        }))
+    }
+
+    /// Another texture over the same texels, clamped and bilinear
+    pub(super) fn from_mip_map(mip_map: Arc<MipMap>) -> Self {
+        Self {
+            mip_map,
+            wrap: (WrapMode::Clamp, WrapMode::Clamp),
+            filter: Filter::Bilinear,
+        }
+    }
+
+    pub(super) fn mip_map(&self) -> &Arc<MipMap> {
+        &self.mip_map
+    }
+
+    /// Bytes of texels held by all the image textures currently loaded
+    pub fn memory_in_use() -> usize {
+        MEMORY_IN_USE.load(Ordering::Relaxed)
+    }
 
-        let u = u.clamp(0.0, 1.0);
-        let v = 1.0 - v.clamp(0.0, 1.0);
+    /// What happens outside of [0, 1] along u and along v
+    pub fn with_wrap(mut self, wrap_u: WrapMode, wrap_v: WrapMode) -> Self {
+        self.wrap = (wrap_u, wrap_v);
+        self
+    }
 
-        let i = (u * width as f64) as u32;
-        let j = (v * height as f64) as u32;
+    /// `Filter::Trilinear` & `Filter::Ewa` build the mip pyramid. They need a
+    /// footprint and fall back to bilinear for lookups without one
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        if matches!(filter, Filter::Trilinear | Filter::Ewa) {
+            self.mip_map.build_pyramid();
+        }
+        self
+    }
 
-        let i = i.clamp(0, width - 1);
-        let j = j.clamp(0, height - 1);
+    /// Color of the texture at (u, v) averaged over `footprint`, using the
+    /// texture's filter
+    pub fn filtered(&self, u: f64, v: f64, footprint: &Footprint) -> Vec3 {
+        match self.filter {
+            Filter::Nearest => self.nearest(u, v),
+            Filter::Bilinear => self.bilinear(0, u, v),
+            Filter::Trilinear => self.trilinear(u, v, footprint),
+            Filter::Ewa => self.ewa(u, v, footprint),
+        }
+    }
 
-        let color_scale = 1.0 / 255.0;
+    fn texel(&self, level: usize, x: i64, y: i64) -> Vec3 {
+        let level = self.mip_map.level(level);
+        let x = wrap(x, level.width, self.wrap.0);
+        let y = wrap(y, level.height, self.wrap.1);
 
-        let pixel = (j * self.bytes_per_scanline + i * self.bytes_per_pixel) as usize;
+        let offset = ((y * level.width + x) * 3) as usize;
 
         Vec3::new(
-            color_scale * (self.image[pixel] as f64),
-            color_scale * (self.image[pixel + 1] as f64),
-            color_scale * (self.image[pixel + 2] as f64),
+            level.texels[offset] as f64,
+            level.texels[offset + 1] as f64,
+            level.texels[offset + 2] as f64,
         )
     }
+
+    /// Position in texels of (u, v) on a level, v = 0 is the bottom of the image
+    fn to_texels(&self, level: usize, u: f64, v: f64) -> (f64, f64) {
+        let level = self.mip_map.level(level);
+        (u * level.width as f64, (1.0 - v) * level.height as f64)
+    }
+
+    fn nearest(&self, u: f64, v: f64) -> Vec3 {
+        let (s, t) = self.to_texels(0, u, v);
+        self.texel(0, s.floor() as i64, t.floor() as i64)
+    }
+
+    fn bilinear(&self, level: usize, u: f64, v: f64) -> Vec3 {
+        let (s, t) = self.to_texels(level, u, v);
+        let (s, t) = (s - 0.5, t - 0.5);
+        let (x, y) = (s.floor(), t.floor());
+        let (fx, fy) = (s - x, t - y);
+        let (x, y) = (x as i64, y as i64);
+
+        self.texel(level, x, y) * ((1.0 - fx) * (1.0 - fy))
+            + self.texel(level, x + 1, y) * (fx * (1.0 - fy))
+            + self.texel(level, x, y + 1) * ((1.0 - fx) * fy)
+            + self.texel(level, x + 1, y + 1) * (fx * fy)
+    }
+
+    /// Fractional mip level whose texels are about `width` wide in texture coordinates
+    fn level_of(&self, width: f64) -> f64 {
+        let size = self.mip_map.full.width.max(self.mip_map.full.height) as f64;
+        (width * size)
+            .max(f64::MIN_POSITIVE)
+            .log2()
+            .clamp(0.0, (self.mip_map.len() - 1) as f64)
+    }
+
+    fn trilinear(&self, u: f64, v: f64, footprint: &Footprint) -> Vec3 {
+        let width = footprint
+            .dudx
+            .abs()
+            .max(footprint.dvdx.abs())
+            .max(footprint.dudy.abs())
+            .max(footprint.dvdy.abs());
+        let level = self.level_of(2.0 * width);
+
+        let below = level.floor() as usize;
+        if below + 1 >= self.mip_map.len() {
+            return self.bilinear(below, u, v);
+        }
+        let t = level - below as f64;
+        self.bilinear(below, u, v) * (1.0 - t) + self.bilinear(below + 1, u, v) * t
+    }
+
+    /// Matt Pharr, Wenzel Jakob & Greg Humphreys, Physically Based Rendering, 10.4.5
+    /// https://pbr-book.org/3ed-2018/Texture/Image_Texture#EllipticallyWeightedAverage
+    fn ewa(&self, u: f64, v: f64, footprint: &Footprint) -> Vec3 {
+        let (mut major, mut minor) = (
+            (footprint.dudx, footprint.dvdx),
+            (footprint.dudy, footprint.dvdy),
+        );
+        let length = |(du, dv): (f64, f64)| (du * du + dv * dv).sqrt();
+        if length(major) < length(minor) {
+            std::mem::swap(&mut major, &mut minor);
+        }
+
+        let major_length = length(major);
+        let mut minor_length = length(minor);
+        if minor_length == 0.0 {
+            return self.bilinear(0, u, v);
+        }
+
+        // Widen footprints that are too long and thin
+        if minor_length * MAX_ANISOTROPY < major_length {
+            let scale = major_length / (minor_length * MAX_ANISOTROPY);
+            minor = (minor.0 * scale, minor.1 * scale);
+            minor_length *= scale;
+        }
+
+        // Pick the level by the short axis, so only the long one needs many texels
+        let level = self.level_of(minor_length);
+        let below = level.floor() as usize;
+        if below + 1 >= self.mip_map.len() {
+            // Footprint covers the whole image, which the last level already averages
+            return self.texel(below, 0, 0);
+        }
+        let t = level - below as f64;
+        self.ewa_level(below, u, v, major, minor) * (1.0 - t)
+            + self.ewa_level(below + 1, u, v, major, minor) * t
+    }
+
+    fn ewa_level(&self, level: usize, u: f64, v: f64, a: (f64, f64), b: (f64, f64)) -> Vec3 {
+        let (s, t) = self.to_texels(level, u, v);
+        let (s, t) = (s - 0.5, t - 0.5);
+
+        // Axes of the ellipse in texels, v runs the other way to the rows
+        let (width, height) = (
+            self.mip_map.level(level).width as f64,
+            self.mip_map.level(level).height as f64,
+        );
+        let (ds0, dt0) = (a.0 * width, -a.1 * height);
+        let (ds1, dt1) = (b.0 * width, -b.1 * height);
+
+        // Implicit ellipse A s^2 + B s t + C t^2 < 1 around the point, grown
+        // by a texel so thin footprints still cover some
+        let a = dt0 * dt0 + dt1 * dt1 + 1.0;
+        let b = -2.0 * (ds0 * dt0 + ds1 * dt1);
+        let c = ds0 * ds0 + ds1 * ds1 + 1.0;
+        let f = a * c - b * b / 4.0;
+        let (a, b, c) = (a / f, b / f, c / f);
+
+        // Box around the ellipse
+        let determinant = 4.0 * a * c - b * b;
+        let s_extent = 2.0 * (determinant * c).sqrt() / determinant;
+        let t_extent = 2.0 * (determinant * a).sqrt() / determinant;
+
+        let mut sum = Vec3::splat(0.0);
+        let mut total = 0.0;
+        for y in (t - t_extent).ceil() as i64..=(t + t_extent).floor() as i64 {
+            let dt = y as f64 - t;
+            for x in (s - s_extent).ceil() as i64..=(s + s_extent).floor() as i64 {
+                let ds = x as f64 - s;
+                let r2 = a * ds * ds + b * ds * dt + c * dt * dt;
+                if r2 < 1.0 {
+                    let weight = (-EWA_ALPHA * r2).exp() - (-EWA_ALPHA).exp();
+                    sum += self.texel(level, x, y) * weight;
+                    total += weight;
+                }
+            }
+        }
+
+        if total > 0.0 {
+            sum / total
+        } else {
+            self.bilinear(level, u, v)
+        }
+    }
+}
+
+/// Undoes the sRGB transfer function on a channel in [0, 1]
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Index of a texel on a row or column `size` long
+fn wrap(i: i64, size: u32, mode: WrapMode) -> u32 {
+    let size = size as i64;
+    let i = match mode {
+        WrapMode::Repeat => i.rem_euclid(size),
+        WrapMode::Clamp => i.clamp(0, size - 1),
+        WrapMode::Mirror => {
+            let i = i.rem_euclid(2 * size);
+            if i >= size {
+                2 * size - 1 - i
+            } else {
+                i
+            }
+        }
+    };
+    i as u32
+}
+
+impl Texture for ImageTexture {
+    /// Without a footprint the trilinear and EWA filters fall back to bilinear
+    fn value(&self, u: f64, v: f64, _p: Vec3) -> Vec3 {
+        match self.filter {
+            Filter::Nearest => self.nearest(u, v),
+            _ => self.bilinear(0, u, v),
+        }
+    }
+
+    fn value_at(&self, hit_rec: &HitRecord) -> Vec3 {
+        match &hit_rec.footprint {
+            Some(footprint) => self.filtered(hit_rec.u, hit_rec.v, footprint),
+            None => self.value(hit_rec.u, hit_rec.v, hit_rec.p),
+        }
+    }
 }
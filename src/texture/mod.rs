@@ -1,17 +1,51 @@
+mod cache;
 mod checker;
 mod image_texture;
 mod perlin;
 mod perlin_noise;
 mod solid;
+mod tinted;
+mod vertex_color;
 
+pub use cache::TextureCache;
 pub use checker::Checker;
-pub use image_texture::ImageTexture;
+pub use image_texture::{ColorSpace, Filter, Footprint, ImageTexture, WrapMode};
 pub use perlin::Perlin;
 pub use perlin_noise::PerlinNoise;
 pub use solid::Solid;
+pub use tinted::Tinted;
+pub use vertex_color::VertexColor;
 
-use crate::types::Vec3;
+use std::sync::Arc;
+
+use crate::{hitable::HitRecord, types::Vec3};
 
 pub trait Texture {
     fn value(&self, u: f64, v: f64, p: Vec3) -> Vec3;
+
+    /// Materials look textures up through this so textures that need more than
+    /// the texture coordinates and hit point can get at the rest of the hit
+    fn value_at(&self, hit_rec: &HitRecord) -> Vec3 {
+        self.value(hit_rec.u, hit_rec.v, hit_rec.p)
+    }
+}
+
+impl<T: Texture + ?Sized> Texture for Arc<T> {
+    fn value(&self, u: f64, v: f64, p: Vec3) -> Vec3 {
+        self.as_ref().value(u, v, p)
+    }
+
+    fn value_at(&self, hit_rec: &HitRecord) -> Vec3 {
+        self.as_ref().value_at(hit_rec)
+    }
+}
+
+impl<T: Texture + ?Sized> Texture for &T {
+    fn value(&self, u: f64, v: f64, p: Vec3) -> Vec3 {
+        (**self).value(u, v, p)
+    }
+
+    fn value_at(&self, hit_rec: &HitRecord) -> Vec3 {
+        (**self).value_at(hit_rec)
+    }
 }
@@ -0,0 +1,24 @@
+use crate::{hitable::HitRecord, types::Vec3, Texture};
+
+/// Multiplies every value of a texture by a color
+#[derive(Clone)]
+pub struct Tinted<T: Texture> {
+    texture: T,
+    tint: Vec3,
+}
+
+impl<T: Texture> Tinted<T> {
+    pub fn new(texture: T, tint: Vec3) -> Self {
+        Self { texture, tint }
+    }
+}
+
+impl<T: Texture> Texture for Tinted<T> {
+    fn value(&self, u: f64, v: f64, p: Vec3) -> Vec3 {
+        self.texture.value(u, v, p) * self.tint
+    }
+
+    fn value_at(&self, hit_rec: &HitRecord) -> Vec3 {
+        self.texture.value_at(hit_rec) * self.tint
+    }
+}
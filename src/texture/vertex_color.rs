@@ -0,0 +1,24 @@
+use crate::{hitable::HitRecord, types::Vec3, Texture};
+
+/// Colors stored on the vertices of a mesh, interpolated across its faces.
+/// Surfaces without vertex colors get `fallback`
+#[derive(Clone)]
+pub struct VertexColor {
+    fallback: Vec3,
+}
+
+impl VertexColor {
+    pub const fn new(fallback: Vec3) -> Self {
+        Self { fallback }
+    }
+}
+
+impl Texture for VertexColor {
+    fn value(&self, _u: f64, _v: f64, _p: Vec3) -> Vec3 {
+        self.fallback
+    }
+
+    fn value_at(&self, hit_rec: &HitRecord) -> Vec3 {
+        hit_rec.color.unwrap_or(self.fallback)
+    }
+}
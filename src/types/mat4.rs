@@ -0,0 +1,155 @@
+use std::ops::Mul;
+
+use crate::types::Vec3;
+
+/// 4x4 matrix for affine transforms of points and directions, stored row major.
+/// The bottom row is expected to be 0, 0, 0, 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    rows: [[f64; 4]; 4],
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4::from_rows([
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+
+    pub const fn from_rows(rows: [[f64; 4]; 4]) -> Self {
+        Self { rows }
+    }
+
+    /// Column major, the layout glTF uses
+    pub fn from_columns(columns: [[f64; 4]; 4]) -> Self {
+        Self::from_rows(columns).transpose()
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        Self::from_rows([
+            [1.0, 0.0, 0.0, offset.x()],
+            [0.0, 1.0, 0.0, offset.y()],
+            [0.0, 0.0, 1.0, offset.z()],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Scale along each axis
+    pub fn scaling(factor: Vec3) -> Self {
+        Self::from_rows([
+            [factor.x(), 0.0, 0.0, 0.0],
+            [0.0, factor.y(), 0.0, 0.0],
+            [0.0, 0.0, factor.z(), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Rotation by `angle` degrees around `axis`, counter clockwise when looking
+    /// down the axis towards the origin. `axis` doesn't need to be normalized
+    pub fn rotation(axis: Vec3, angle: f64) -> Self {
+        let axis = axis.unit_vector();
+        let (x, y, z) = (axis.x(), axis.y(), axis.z());
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let k = 1.0 - cos;
+
+        // Rodrigues' rotation formula
+        Self::from_rows([
+            [
+                k * x * x + cos,
+                k * x * y - sin * z,
+                k * x * z + sin * y,
+                0.0,
+            ],
+            [
+                k * x * y + sin * z,
+                k * y * y + cos,
+                k * y * z - sin * x,
+                0.0,
+            ],
+            [
+                k * x * z - sin * y,
+                k * y * z + sin * x,
+                k * z * z + cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = self.rows[j][i];
+            }
+        }
+        Self::from_rows(rows)
+    }
+
+    /// Determinant of the upper 3x3, how much the matrix scales volumes
+    pub fn determinant(&self) -> f64 {
+        let row = |i: usize| Vec3::new(self.rows[i][0], self.rows[i][1], self.rows[i][2]);
+        row(0).dot(&row(1).cross(&row(2)))
+    }
+
+    /// `None` if the matrix is singular, e.g. scales an axis down to nothing
+    pub fn inverse(&self) -> Option<Self> {
+        // Gauss-Jordan elimination with partial pivoting
+        let mut m = self.rows;
+        let mut inverse = Self::IDENTITY.rows;
+
+        for col in 0..4 {
+            let pivot = (col..4).max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))?;
+            if m[pivot][col].abs() < f64::EPSILON {
+                return None;
+            }
+            m.swap(col, pivot);
+            inverse.swap(col, pivot);
+
+            let scale = 1.0 / m[col][col];
+            for j in 0..4 {
+                m[col][j] *= scale;
+                inverse[col][j] *= scale;
+            }
+
+            for row in 0..4 {
+                let factor = m[row][col];
+                if row == col || factor == 0.0 {
+                    continue;
+                }
+                for j in 0..4 {
+                    m[row][j] -= factor * m[col][j];
+                    inverse[row][j] -= factor * inverse[col][j];
+                }
+            }
+        }
+
+        Some(Self::from_rows(inverse))
+    }
+
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        self.transform_vector(p) + Vec3::new(self.rows[0][3], self.rows[1][3], self.rows[2][3])
+    }
+
+    /// Transforms a direction, which isn't affected by the translation
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let row = |i: usize| Vec3::new(self.rows[i][0], self.rows[i][1], self.rows[i][2]);
+        Vec3::new(row(0).dot(&v), row(1).dot(&v), row(2).dot(&v))
+    }
+}
+
+/// `a * b` applies `b` first and then `a`
+impl Mul for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, other: Mat4) -> Mat4 {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = (0..4).map(|k| self.rows[i][k] * other.rows[k][j]).sum();
+            }
+        }
+        Self::from_rows(rows)
+    }
+}
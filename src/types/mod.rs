@@ -1,10 +1,14 @@
 mod color;
 mod dimension;
+mod mat4;
+mod quaternion;
 mod ray;
 
 pub use color::Color;
 pub use dimension::{Dimension, X, Y, Z};
-pub use ray::Ray;
+pub use mat4::Mat4;
+pub use quaternion::Quaternion;
+pub use ray::{OffsetRay, Ray, RayDifferentials};
 
 #[cfg(not(target_arch = "x86_64"))]
 mod vec3;
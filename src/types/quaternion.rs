@@ -0,0 +1,111 @@
+use crate::types::{Mat4, Vec3};
+
+/// Unit quaternion describing a rotation, used where rotations have to be
+/// interpolated smoothly
+#[derive(Debug, Clone, Copy)]
+pub struct Quaternion {
+    w: f64,
+    v: Vec3,
+}
+
+impl Quaternion {
+    pub fn identity() -> Self {
+        Self {
+            w: 1.0,
+            v: Vec3::splat(0.0),
+        }
+    }
+
+    /// Rotation by `angle` degrees around `axis`, same direction as `Mat4::rotation`
+    pub fn from_axis_angle(axis: Vec3, angle: f64) -> Self {
+        let (sin, cos) = (angle.to_radians() / 2.0).sin_cos();
+        Self {
+            w: cos,
+            v: axis.unit_vector() * sin,
+        }
+    }
+
+    fn dot(&self, other: &Self) -> f64 {
+        self.w * other.w + self.v.dot(&other.v)
+    }
+
+    /// Rotation that undoes this one
+    pub fn conjugate(self) -> Self {
+        Self {
+            w: self.w,
+            v: -self.v,
+        }
+    }
+
+    /// Angle in radians of the rotation that takes this one to `other`
+    pub fn angle_to(&self, other: &Self) -> f64 {
+        2.0 * self.dot(other).abs().min(1.0).acos()
+    }
+
+    /// Spherical linear interpolation, turns at a constant speed along the
+    /// shortest way from `self` at `t` = 0 to `other` at `t` = 1
+    pub fn slerp(self, other: Self, t: f64) -> Self {
+        let cos = self.dot(&other);
+
+        // q and -q are the same rotation, pick the one that's closer
+        let (other, cos) = if cos < 0.0 {
+            (
+                Self {
+                    w: -other.w,
+                    v: -other.v,
+                },
+                -cos,
+            )
+        } else {
+            (other, cos)
+        };
+
+        let (a, b) = if cos > 0.9995 {
+            // Nearly the same rotation, a straight line is fine and avoids dividing by ~0
+            (1.0 - t, t)
+        } else {
+            let theta = cos.acos();
+            let sin = theta.sin();
+            (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+        };
+
+        Self {
+            w: a * self.w + b * other.w,
+            v: self.v * a + other.v * b,
+        }
+        .normalize()
+    }
+
+    fn normalize(self) -> Self {
+        let length = self.dot(&self).sqrt();
+        Self {
+            w: self.w / length,
+            v: self.v / length,
+        }
+    }
+
+    pub fn to_mat4(self) -> Mat4 {
+        let (w, x, y, z) = (self.w, self.v.x(), self.v.y(), self.v.z());
+        Mat4::from_rows([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+                0.0,
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+                0.0,
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+}
@@ -1,4 +1,4 @@
-use rand::prelude::SmallRng;
+use rand::{prelude::SmallRng, Rng};
 
 use crate::{hitable::Hitable, types::Vec3};
 
@@ -6,6 +6,23 @@ pub struct Ray {
     pub origin: Vec3,
     pub direction: Vec3,
     time: f64,
+    /// Rays through the neighbouring pixels, for working out how much of a
+    /// texture a pixel covers. Only camera rays and their mirror and glass
+    /// bounces have them
+    pub differentials: Option<RayDifferentials>,
+}
+
+/// A pair of rays offset from a main ray by one pixel along x and one along y
+#[derive(Debug, Clone, Copy)]
+pub struct RayDifferentials {
+    pub x: OffsetRay,
+    pub y: OffsetRay,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OffsetRay {
+    pub origin: Vec3,
+    pub direction: Vec3,
 }
 
 impl Ray {
@@ -14,9 +31,15 @@ impl Ray {
             origin,
             direction,
             time,
+            differentials: None,
         }
     }
 
+    pub fn with_differentials(mut self, differentials: Option<RayDifferentials>) -> Self {
+        self.differentials = differentials;
+        self
+    }
+
     #[inline]
     pub fn point_at_parameter(&self, t: f64) -> Vec3 {
         self.origin + self.direction * t
@@ -26,14 +49,19 @@ impl Ray {
         self.time
     }
 
-    pub fn color<T: Hitable>(
+    /// Light arriving along the ray. Diffuse surfaces send half of their rays
+    /// straight at `lights` instead of wherever their material picks
+    pub fn color<T: Hitable, L: Hitable + ?Sized>(
         &self,
         world: &T,
+        lights: Option<&L>,
         rng: &mut SmallRng,
         background: &Vec3,
         depth: u32,
     ) -> Vec3 {
-        if let Some(hit_rec) = world.hit(self, 0.001, std::f64::MAX) {
+        if let Some(mut hit_rec) = world.hit(self, 0.001, std::f64::MAX) {
+            hit_rec.set_footprint(self);
+
             if depth >= 50 {
                 Vec3::splat(0.0f64)
             } else {
@@ -43,11 +71,39 @@ impl Ray {
                 if let (attenuation, pdf, Some(scattered_ray)) =
                     material.scatter(self, &hit_rec, rng)
                 {
-                    emitted_color
-                        + attenuation
-                            * material.scatter_pdf(self, &hit_rec, &scattered_ray)
-                            * scattered_ray.color(world, rng, background, depth + 1)
-                            / pdf
+                    let (scattered_ray, pdf) = match lights {
+                        // Either way of picking could have made the ray, so it's
+                        // weighed by the average of both densities
+                        Some(lights)
+                            if material
+                                .sampling_pdf(self, &hit_rec, &scattered_ray)
+                                .is_some() =>
+                        {
+                            let scattered_ray = if rng.gen::<bool>() {
+                                let direction = lights.random(hit_rec.p, self.time(), rng);
+                                Ray::new(hit_rec.p, direction, self.time())
+                            } else {
+                                scattered_ray
+                            };
+                            let material_pdf = material
+                                .sampling_pdf(self, &hit_rec, &scattered_ray)
+                                .unwrap_or(0.0);
+
+                            let pdf = 0.5 * material_pdf + 0.5 * lights.pdf_value(&scattered_ray);
+                            (scattered_ray, pdf)
+                        }
+                        _ => (scattered_ray, pdf),
+                    };
+
+                    if pdf > 0.0 {
+                        emitted_color
+                            + attenuation
+                                * material.scatter_pdf(self, &hit_rec, &scattered_ray)
+                                * scattered_ray.color(world, lights, rng, background, depth + 1)
+                                / pdf
+                    } else {
+                        emitted_color
+                    }
                 } else {
                     emitted_color
                 }
@@ -57,3 +113,27 @@ impl Ray {
         }
     }
 }
+
+impl RayDifferentials {
+    /// Applies `f` to both offset rays
+    pub fn map(self, f: impl Fn(OffsetRay) -> OffsetRay) -> Self {
+        Self {
+            x: f(self.x),
+            y: f(self.y),
+        }
+    }
+}
+
+impl OffsetRay {
+    /// Where the ray crosses the plane through `point` that faces `normal`,
+    /// `None` if it runs along the plane
+    pub fn on_plane(&self, point: Vec3, normal: Vec3) -> Option<Vec3> {
+        let denominator = normal.dot(&self.direction);
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = normal.dot(&(point - self.origin)) / denominator;
+        Some(self.origin + self.direction * t)
+    }
+}